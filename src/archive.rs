@@ -0,0 +1,49 @@
+use crate::error::NesError;
+use flate2::read::GzDecoder;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+// Unpacks a ROM image stored in a .zip or .gz file, recognised by their magic numbers. Other
+// files are assumed to already be a ROM image and are returned as is. From a zip the named
+// entry is used, or the first .nes entry when no name is given
+pub fn extract_rom(data: Vec<u8>, entry_name: Option<&str>) -> Result<Vec<u8>, NesError> {
+    if data.starts_with(b"PK\x03\x04") {
+        extract_zip(data, entry_name)
+    } else if data.starts_with(&[0x1F, 0x8B]) {
+        let mut image = Vec::new();
+        GzDecoder::new(data.as_slice())
+            .read_to_end(&mut image)
+            .map_err(|e| NesError::CorruptArchive(e.to_string()))?;
+        Ok(image)
+    } else {
+        Ok(data)
+    }
+}
+
+fn extract_zip(data: Vec<u8>, entry_name: Option<&str>) -> Result<Vec<u8>, NesError> {
+    let mut archive =
+        ZipArchive::new(Cursor::new(data)).map_err(|e| NesError::CorruptArchive(e.to_string()))?;
+
+    // Search in archive order so the first .nes entry is the one listed first
+    let index = (0..archive.len())
+        .find(|&index| {
+            archive
+                .name_for_index(index)
+                .is_some_and(|file_name| match entry_name {
+                    Some(name) => file_name == name || file_name.ends_with(&format!("/{}", name)),
+                    None => file_name.to_ascii_lowercase().ends_with(".nes"),
+                })
+        })
+        .ok_or_else(|| match entry_name {
+            Some(name) => NesError::ArchiveEntryNotFound(name.to_string()),
+            None => NesError::NoRomInArchive,
+        })?;
+
+    let mut file = archive
+        .by_index(index)
+        .map_err(|e| NesError::CorruptArchive(e.to_string()))?;
+    let mut image = Vec::new();
+    file.read_to_end(&mut image)
+        .map_err(|e| NesError::CorruptArchive(e.to_string()))?;
+    Ok(image)
+}
@@ -1,20 +1,96 @@
+use crate::error::{NesError, UnmappedAccessPolicy};
+use crate::mappers::{SharedMapper, create_mapper};
+use crate::nes::Region;
 use crate::ppu::Ppu;
 use crate::rom::Rom;
 
 pub struct Bus {
     pub ram: [u8; 0x800],
-    pub rom: Rom,
+    pub mapper: SharedMapper,
     pub ppu: Ppu,
+    pub region: Region,
+    pub cycles: u64,
+    pub unmapped_access_policy: UnmappedAccessPolicy,
+    open_bus: u8,
+    fault: Option<NesError>,
+    ppu_dot_fifths: u32,
+    oam_dma_page: Option<u8>,
 }
 
 impl Bus {
-    pub fn new(rom: Rom) -> Self {
-        let ppu = Ppu::new(rom.cartridge_info.clone(), rom.chr_rom.clone());
+    pub fn new(rom: Rom, region: Region) -> Result<Self, NesError> {
+        let mapper = create_mapper(rom)?;
+        let ppu = Ppu::new(mapper.clone(), region);
 
-        Bus {
+        Ok(Bus {
             ram: [0; 0x800],
-            rom,
+            mapper,
             ppu,
+            region,
+            cycles: 0,
+            unmapped_access_policy: UnmappedAccessPolicy::OpenBus,
+            open_bus: 0,
+            fault: None,
+            ppu_dot_fifths: 0,
+            oam_dma_page: None,
+        })
+    }
+
+    // Every CPU bus access takes one cycle, during which the rest of the console catches up
+    pub fn tick(&mut self) {
+        self.cycles += 1;
+        self.mapper.borrow_mut().cpu_tick();
+        self.ppu_dot_fifths += self.region.ppu_dots_per_cycle_fifths();
+        while self.ppu_dot_fifths >= 5 {
+            self.ppu_dot_fifths -= 5;
+            self.ppu.tick();
+        }
+    }
+
+    pub fn nmi_line(&self) -> bool {
+        self.ppu.nmi_line()
+    }
+
+    pub fn irq_line(&self) -> bool {
+        self.mapper.borrow().irq()
+    }
+
+    // Returns the first unmapped access since the last call, unless the policy is to ignore them
+    pub fn take_fault(&mut self) -> Option<NesError> {
+        self.fault.take()
+    }
+
+    // Copies the page written to $4014 into OAM. The CPU halts for one cycle, waits another when
+    // the DMA would start on an odd cycle, then each byte takes a read and a write cycle, for
+    // 513 or 514 cycles in total. Returns the number of cycles the CPU was stalled for
+    pub fn run_oam_dma(&mut self) -> u16 {
+        let Some(page) = self.oam_dma_page.take() else {
+            return 0;
+        };
+        let start = self.cycles;
+        let odd_cycle = self.cycles % 2 == 1;
+        self.tick();
+        if odd_cycle {
+            self.tick();
+        }
+        for low in 0..=0xFF {
+            let value = self.read((page as u16) << 8 | low);
+            self.tick();
+            self.ppu.write_register(0x2004, value);
+        }
+        (self.cycles - start) as u16
+    }
+
+    fn report_unmapped_access(&mut self, error: NesError) {
+        if self.unmapped_access_policy != UnmappedAccessPolicy::OpenBus && self.fault.is_none() {
+            self.fault = Some(error);
+        }
+    }
+
+    fn read_cartridge(&self, addr: u16) -> Option<u8> {
+        match addr {
+            0x4020.. => self.mapper.borrow().cpu_read(addr),
+            _ => None,
         }
     }
 
@@ -23,44 +99,46 @@ impl Bus {
             0x0000..=0x1FFF => self.ram[(addr & 0x07FF) as usize],
             0x2000..=0x3FFF => self.ppu.peek_register(addr & 0x2007),
             0x4000..=0x4017 => 0, // TODO: implement audio registers
-            0x8000.. => {
-                let rom_index = (addr - 0x8000) as usize;
-                if rom_index < self.rom.prg_rom.len() {
-                    self.rom.prg_rom[rom_index]
-                } else {
-                    panic!("Indexed ROM out of bounds {}", rom_index);
-                }
-            }
-            _ => todo!("Unimplemented memory access 0x{:04X}", addr),
+            0x4018.. => self.read_cartridge(addr).unwrap_or(self.open_bus),
         }
     }
-    
+
     pub fn read(&mut self, addr: u16) -> u8 {
-        match addr {
+        self.tick();
+        let value = match addr {
             0x0000..=0x1FFF => self.ram[(addr & 0x07FF) as usize],
             0x2000..=0x3FFF => self.ppu.read_register(addr & 0x2007),
             0x4000..=0x4017 => 0, // TODO: implement audio registers
-            0x8000.. => {
-                let rom_index = (addr - 0x8000) as usize;
-                if rom_index < self.rom.prg_rom.len() {
-                    self.rom.prg_rom[rom_index]
-                } else {
-                    panic!("Indexed ROM out of bounds {}", rom_index);
+            0x4018.. => match self.read_cartridge(addr) {
+                Some(value) => value,
+                None => {
+                    self.report_unmapped_access(NesError::UnmappedRead(addr));
+                    self.open_bus
                 }
-            }
-            _ => todo!("Unimplemented memory access 0x{:04X}", addr),
-        }
+            },
+        };
+        self.open_bus = value;
+        value
     }
 
     pub fn write(&mut self, address: u16, value: u8) {
+        self.tick();
+        self.open_bus = value;
         match address {
             0x0000..=0x1FFF => self.ram[(address & 0x07FF) as usize] = value,
             0x2000..=0x3FFF => {
                 let ppu_address = address & 0x2007;
                 self.ppu.write_register(ppu_address, value);
             }
+            0x4014 => self.oam_dma_page = Some(value),
             0x4000..=0x4017 => {} // TODO: implement audio registers
-            _ => todo!("Unimplemented memory access 0x{:04X}", address),
+            0x4018.. => {
+                let handled =
+                    address >= 0x4020 && self.mapper.borrow_mut().cpu_write(address, value);
+                if !handled {
+                    self.report_unmapped_access(NesError::UnmappedWrite(address));
+                }
+            }
         }
     }
 }
@@ -2,19 +2,70 @@
 pub enum Mirroring {
     Horizontal,
     Vertical,
+    SingleScreenA,
+    SingleScreenB,
     FourScreen,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderFormat {
+    // The original iNES layout, where only flags 6 and the low nibble of the mapper are reliable
+    Archaic,
+    // An archaic header with junk in bytes 7-15, usually a ripper tag such as "DiskDude!"
+    Dirty,
+    INes,
+    Nes2,
+}
+
+impl HeaderFormat {
+    pub fn detect(header: &[u8; 16]) -> Self {
+        match header[7] & 0x0C {
+            0x08 => HeaderFormat::Nes2,
+            0x00 if header[12..16].iter().all(|&byte| byte == 0) => HeaderFormat::INes,
+            _ if &header[7..16] == b"DiskDude!" => HeaderFormat::Dirty,
+            0x04 => HeaderFormat::Archaic,
+            _ => HeaderFormat::Dirty,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timing {
+    Ntsc,
+    Pal,
+    // Runs on either, we pick NTSC
+    MultiRegion,
+    Dendy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleType {
+    Nes,
+    VsSystem,
+    Playchoice10,
+    // NES 2.0 extended console type from byte 13
+    Extended(u8),
+}
+
 #[derive(Debug, Clone)]
 pub struct CartridgeInfo {
+    pub header_format: HeaderFormat,
     pub mirroring: Mirroring,
     pub has_battery_backed_ram: bool,
     pub has_trainer: bool,
     pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub timing: Timing,
+    pub console_type: ConsoleType,
+    // NES 2.0 default expansion device, 0 when unspecified
+    pub expansion_device: u8,
 }
 
 impl CartridgeInfo {
     pub fn from_header(header: &[u8; 16]) -> Self {
+        let header_format = HeaderFormat::detect(header);
         let flags_6 = header[6];
         let chr_rom_size = header[5] as usize * 8192;
 
@@ -26,11 +77,64 @@ impl CartridgeInfo {
             Mirroring::Horizontal
         };
 
-        CartridgeInfo {
+        let mut info = CartridgeInfo {
+            header_format,
             mirroring,
             has_battery_backed_ram: flags_6 & 0x02 != 0,
             has_trainer: flags_6 & 0x04 != 0,
             chr_ram_size: if chr_rom_size == 0 { 8192 } else { 0 },
+            chr_nvram_size: 0,
+            prg_ram_size: 8192,
+            prg_nvram_size: 0,
+            timing: Timing::Ntsc,
+            console_type: ConsoleType::Nes,
+            expansion_device: 0,
+        };
+
+        match header_format {
+            HeaderFormat::Archaic | HeaderFormat::Dirty => {}
+            HeaderFormat::INes => {
+                // Byte 8 counts 8 KB units of PRG RAM, with 0 meaning 8 KB for compatibility
+                info.prg_ram_size = header[8].max(1) as usize * 8192;
+                if header[9] & 0x01 != 0 {
+                    info.timing = Timing::Pal;
+                }
+                info.console_type = console_type(header);
+            }
+            HeaderFormat::Nes2 => {
+                info.prg_ram_size = shift_size(header[10] & 0x0F);
+                info.prg_nvram_size = shift_size(header[10] >> 4);
+                info.chr_ram_size = shift_size(header[11] & 0x0F);
+                info.chr_nvram_size = shift_size(header[11] >> 4);
+                // Headers that leave out CHR RAM on a board without CHR ROM are wrong
+                if chr_rom_size == 0 && header[9] & 0xF0 == 0 && info.chr_ram_size == 0 {
+                    info.chr_ram_size = 8192;
+                }
+                info.timing = match header[12] & 0x03 {
+                    0 => Timing::Ntsc,
+                    1 => Timing::Pal,
+                    2 => Timing::MultiRegion,
+                    _ => Timing::Dendy,
+                };
+                info.console_type = console_type(header);
+                info.expansion_device = header[15] & 0x3F;
+            }
         }
+
+        info
+    }
+}
+
+// NES 2.0 RAM sizes are stored as a shift count of 64 bytes, with 0 meaning no RAM
+fn shift_size(shift: u8) -> usize {
+    if shift == 0 { 0 } else { 64 << shift }
+}
+
+fn console_type(header: &[u8; 16]) -> ConsoleType {
+    match header[7] & 0x03 {
+        0 => ConsoleType::Nes,
+        1 => ConsoleType::VsSystem,
+        2 => ConsoleType::Playchoice10,
+        _ => ConsoleType::Extended(header[13] & 0x0F),
     }
 }
@@ -1,6 +1,10 @@
 use crate::bus::Bus;
+use crate::error::{JamPolicy, NesError, UnmappedAccessPolicy};
 use crate::opcodes::OPCODES;
 
+const NMI_VECTOR: u16 = 0xFFFA;
+const IRQ_VECTOR: u16 = 0xFFFE;
+
 pub struct Cpu {
     program_counter: u16,
     a: u8,
@@ -13,7 +17,17 @@ pub struct Cpu {
     flag_decimal: bool,
     flag_overflow: bool,
     flag_negative: bool,
+    nmi_line_previous: bool,
+    nmi_pending: bool,
+    irq_pending: bool,
     pub halted: bool,
+    pub jam_policy: JamPolicy,
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Cpu {
@@ -25,41 +39,48 @@ impl Cpu {
             y: 0,
             stack_pointer: 0,
             halted: false,
+            jam_policy: JamPolicy::Halt,
             flag_carry: false,
             flag_overflow: false,
             flag_negative: false,
             flag_zero: false,
             flag_decimal: false,
             flag_interrupt_disable: false,
+            nmi_line_previous: false,
+            nmi_pending: false,
+            irq_pending: false,
         }
     }
 
     fn get_operand_mode(&self, opcode: u8) -> u8 {
         match opcode {
             // Single Byte Instructions (Implied/Accumulator)
-            0x00 | 0x08 | 0x18 | 0x28 | 0x38 | 0x40 | 0x48 | 0x58 | 0x60 | 0x68 | 0x78 | 0x88
-            | 0x8A | 0x98 | 0x9A | 0xA8 | 0xAA | 0xB8 | 0xBA | 0xC8 | 0xCA | 0xD8 | 0xE8 | 0xEA
-            | 0xF8 | 0x0A | 0x2A | 0x4A | 0x6A => 1,
+            0x00 | 0x02 | 0x08 | 0x0A | 0x12 | 0x18 | 0x1A | 0x22 | 0x28 | 0x2A | 0x32 | 0x38
+            | 0x3A | 0x40 | 0x42 | 0x48 | 0x4A | 0x52 | 0x58 | 0x5A | 0x60 | 0x62 | 0x68 | 0x6A
+            | 0x72 | 0x78 | 0x7A | 0x88 | 0x8A | 0x92 | 0x98 | 0x9A | 0xA8 | 0xAA | 0xB2 | 0xB8
+            | 0xBA | 0xC8 | 0xCA | 0xD2 | 0xD8 | 0xDA | 0xE8 | 0xEA | 0xF2 | 0xF8 | 0xFA => 1,
 
             // 2 Byte Instructions (Immediate, Zero Page, Zero Page Indexed, Indirect Indexed, Relative)
-            0x01 | 0x05 | 0x06 | 0x09 | 0x10 | 0x11 | 0x15 | 0x16 | 0x21 | 0x24 | 0x25 | 0x26
-            | 0x29 | 0x30 | 0x31 | 0x35 | 0x36 | 0x41 | 0x45 | 0x46 | 0x49 | 0x50 | 0x51 | 0x55
-            | 0x56 | 0x61 | 0x65 | 0x66 | 0x69 | 0x70 | 0x71 | 0x75 | 0x76 | 0x81 | 0x84 | 0x85
-            | 0x86 | 0x90 | 0x91 | 0x94 | 0x95 | 0x96 | 0xA0 | 0xA1 | 0xA2 | 0xA4 | 0xA5 | 0xA6
-            | 0xA9 | 0xB0 | 0xB1 | 0xB4 | 0xB5 | 0xB6 | 0xC0 | 0xC1 | 0xC4 | 0xC5 | 0xC6 | 0xC9
-            | 0xD0 | 0xD1 | 0xD5 | 0xD6 | 0xE0 | 0xE1 | 0xE4 | 0xE5 | 0xE6 | 0xE9 | 0xF0 | 0xF1
-            | 0xF5 | 0xF6 => 2,
+            0x01 | 0x03 | 0x04 | 0x05 | 0x06 | 0x07 | 0x09 | 0x0B | 0x10 | 0x11 | 0x13 | 0x14
+            | 0x15 | 0x16 | 0x17 | 0x21 | 0x23 | 0x24 | 0x25 | 0x26 | 0x27 | 0x29 | 0x2B | 0x30
+            | 0x31 | 0x33 | 0x34 | 0x35 | 0x36 | 0x37 | 0x41 | 0x43 | 0x44 | 0x45 | 0x46 | 0x47
+            | 0x49 | 0x4B | 0x50 | 0x51 | 0x53 | 0x54 | 0x55 | 0x56 | 0x57 | 0x61 | 0x63 | 0x64
+            | 0x65 | 0x66 | 0x67 | 0x69 | 0x6B | 0x70 | 0x71 | 0x73 | 0x74 | 0x75 | 0x76 | 0x77
+            | 0x80 | 0x81 | 0x82 | 0x83 | 0x84 | 0x85 | 0x86 | 0x87 | 0x89 | 0x8B | 0x90 | 0x91
+            | 0x93 | 0x94 | 0x95 | 0x96 | 0x97 | 0xA0 | 0xA1 | 0xA2 | 0xA3 | 0xA4 | 0xA5 | 0xA6
+            | 0xA7 | 0xA9 | 0xAB | 0xB0 | 0xB1 | 0xB3 | 0xB4 | 0xB5 | 0xB6 | 0xB7 | 0xC0 | 0xC1
+            | 0xC2 | 0xC3 | 0xC4 | 0xC5 | 0xC6 | 0xC7 | 0xC9 | 0xCB | 0xD0 | 0xD1 | 0xD3 | 0xD4
+            | 0xD5 | 0xD6 | 0xD7 | 0xE0 | 0xE1 | 0xE2 | 0xE3 | 0xE4 | 0xE5 | 0xE6 | 0xE7 | 0xE9
+            | 0xEB | 0xF0 | 0xF1 | 0xF3 | 0xF4 | 0xF5 | 0xF6 | 0xF7 => 2,
 
             // 3 Byte Instructions (Absolute, Absolute Indexed, Indirect)
-            0x0D | 0x0E | 0x19 | 0x1D | 0x1E | 0x20 | 0x2C | 0x2D | 0x2E | 0x39 | 0x3D | 0x3E
-            | 0x4C | 0x4D | 0x4E | 0x59 | 0x5D | 0x5E | 0x6C | 0x6D | 0x6E | 0x79 | 0x7D | 0x7E
-            | 0x8C | 0x8D | 0x8E | 0x99 | 0x9D | 0xAC | 0xAD | 0xAE | 0xB9 | 0xBC | 0xBD | 0xBE
-            | 0xCC | 0xCD | 0xCE | 0xD9 | 0xDD | 0xDE | 0xEC | 0xED | 0xEE | 0xF9 | 0xFD | 0xFE => {
-                3
-            }
-
-            // Any other instructions
-            _ => 1,
+            0x0C | 0x0D | 0x0E | 0x0F | 0x19 | 0x1B | 0x1C | 0x1D | 0x1E | 0x1F | 0x20 | 0x2C
+            | 0x2D | 0x2E | 0x2F | 0x39 | 0x3B | 0x3C | 0x3D | 0x3E | 0x3F | 0x4C | 0x4D | 0x4E
+            | 0x4F | 0x59 | 0x5B | 0x5C | 0x5D | 0x5E | 0x5F | 0x6C | 0x6D | 0x6E | 0x6F | 0x79
+            | 0x7B | 0x7C | 0x7D | 0x7E | 0x7F | 0x8C | 0x8D | 0x8E | 0x8F | 0x99 | 0x9B | 0x9C
+            | 0x9D | 0x9E | 0x9F | 0xAC | 0xAD | 0xAE | 0xAF | 0xB9 | 0xBB | 0xBC | 0xBD | 0xBE
+            | 0xBF | 0xCC | 0xCD | 0xCE | 0xCF | 0xD9 | 0xDB | 0xDC | 0xDD | 0xDE | 0xDF | 0xEC
+            | 0xED | 0xEE | 0xEF | 0xF9 | 0xFB | 0xFC | 0xFD | 0xFE | 0xFF => 3,
         }
     }
 
@@ -103,11 +124,61 @@ impl Cpu {
     }
 
     pub fn reset(&mut self, bus: &mut Bus) {
+        // Reset runs the interrupt sequence with its stack writes turned into reads, taking
+        // 7 cycles and leaving the stack pointer 3 lower
+        self.dummy_read(bus);
+        self.dummy_read(bus);
+        for _ in 0..3 {
+            bus.read(0x100 + self.stack_pointer as u16);
+            self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        }
         let pc_low = bus.read(0xFFFC);
         let pc_high = bus.read(0xFFFD);
         self.program_counter = (pc_high as u16 * 0x100) + pc_low as u16;
         self.flag_interrupt_disable = true;
-        self.stack_pointer = 0xFD
+    }
+
+    // NMI is edge triggered, so it is latched when the line goes from low to high
+    fn detect_nmi(&mut self, bus: &Bus) -> bool {
+        let nmi_line = bus.nmi_line();
+        if nmi_line && !self.nmi_line_previous {
+            self.nmi_pending = true;
+        }
+        self.nmi_line_previous = nmi_line;
+        self.nmi_pending
+    }
+
+    // Interrupts are polled at the end of each instruction. IRQ is level triggered and masked by
+    // the interrupt disable flag as it was when polling happened
+    fn poll_interrupts(&mut self, bus: &Bus, interrupt_disable: bool) {
+        self.detect_nmi(bus);
+        self.irq_pending = bus.irq_line() && !interrupt_disable;
+    }
+
+    // Shared by BRK, IRQ and NMI. An NMI detected before the status is pushed hijacks the
+    // sequence and takes over the vector, while the pushed break flag is left as it was
+    fn interrupt(&mut self, bus: &mut Bus, vector: u16, flag_break: bool) -> u8 {
+        self.push(bus, (self.program_counter >> 8) as u8);
+        self.push(bus, self.program_counter as u8);
+
+        let vector = if self.detect_nmi(bus) {
+            self.nmi_pending = false;
+            NMI_VECTOR
+        } else {
+            vector
+        };
+
+        let status = self.get_status_register(flag_break);
+        self.push(bus, status);
+
+        self.flag_interrupt_disable = true;
+        self.irq_pending = false;
+
+        let destination_address_low = bus.read(vector);
+        let destination_address_high = bus.read(vector.wrapping_add(1));
+        self.program_counter =
+            (destination_address_high as u16) << 8 | destination_address_low as u16;
+        7
     }
 
     fn push(&mut self, bus: &mut Bus, value: u8) {
@@ -117,8 +188,7 @@ impl Cpu {
 
     fn pull(&mut self, bus: &mut Bus) -> u8 {
         self.stack_pointer = self.stack_pointer.wrapping_add(1);
-        let value = bus.read(0x100 + self.stack_pointer as u16);
-        value
+        bus.read(0x100 + self.stack_pointer as u16)
     }
 
     fn get_status_register(&self, flag_break: bool) -> u8 {
@@ -155,31 +225,44 @@ impl Cpu {
         (value_high as u16) << 8 | value_low as u16
     }
 
-    fn read_absolute_addressed_x_indexed(&mut self, bus: &mut Bus) -> u16 {
-        let value_low = bus.read(self.program_counter);
-        self.program_counter = self.program_counter.wrapping_add(1);
-        let value_high = bus.read(self.program_counter);
-        self.program_counter = self.program_counter.wrapping_add(1);
-        ((value_high as u16) << 8 | value_low as u16).wrapping_add(self.x as u16)
+    // Indexed addressing first reads from the address before its high byte is fixed up. Reads
+    // only pay for that extra cycle when a page is crossed, stores and read-modify-writes always do
+    fn index_address(
+        &mut self,
+        bus: &mut Bus,
+        base: u16,
+        index: u8,
+        is_write: bool,
+    ) -> (u16, bool) {
+        let address = base.wrapping_add(index as u16);
+        let page_crossed = (base ^ address) & 0xFF00 != 0;
+        if page_crossed || is_write {
+            bus.read((base & 0xFF00) | (address & 0x00FF));
+        }
+        (address, page_crossed)
     }
 
-    fn read_absolute_addressed_y_indexed(&mut self, bus: &mut Bus) -> u16 {
-        let value_low = bus.read(self.program_counter);
-        self.program_counter = self.program_counter.wrapping_add(1);
-        let value_high = bus.read(self.program_counter);
-        self.program_counter = self.program_counter.wrapping_add(1);
-        ((value_high as u16) << 8 | value_low as u16).wrapping_add(self.y as u16)
+    fn read_absolute_addressed_x_indexed(&mut self, bus: &mut Bus, is_write: bool) -> (u16, bool) {
+        let base = self.read_absolute_addressed(bus);
+        self.index_address(bus, base, self.x, is_write)
+    }
+
+    fn read_absolute_addressed_y_indexed(&mut self, bus: &mut Bus, is_write: bool) -> (u16, bool) {
+        let base = self.read_absolute_addressed(bus);
+        self.index_address(bus, base, self.y, is_write)
     }
 
     fn read_zero_page_addressed_x_indexed(&mut self, bus: &mut Bus) -> u16 {
         let address = bus.read(self.program_counter);
         self.program_counter = self.program_counter.wrapping_add(1);
+        bus.read(address as u16);
         address.wrapping_add(self.x) as u16
     }
 
     fn read_zero_page_addressed_y_indexed(&mut self, bus: &mut Bus) -> u16 {
         let address = bus.read(self.program_counter);
         self.program_counter = self.program_counter.wrapping_add(1);
+        bus.read(address as u16);
         address.wrapping_add(self.y) as u16
     }
 
@@ -196,41 +279,59 @@ impl Cpu {
     }
 
     fn read_indirect_addressed_x_indexed(&mut self, bus: &mut Bus) -> u16 {
-        let address = bus.read(self.program_counter).wrapping_add(self.x);
+        let pointer = bus.read(self.program_counter);
         self.program_counter = self.program_counter.wrapping_add(1);
+        bus.read(pointer as u16);
+        let address = pointer.wrapping_add(self.x);
         let value_low = bus.read(address as u16);
         let value_high = bus.read(address.wrapping_add(1) as u16);
         (value_high as u16) << 8 | value_low as u16
     }
 
-    fn read_indirect_addressed_y_indexed(&mut self, bus: &mut Bus) -> u16 {
+    fn read_zero_page_pointer(&mut self, bus: &mut Bus) -> u16 {
         let address = bus.read(self.program_counter);
         self.program_counter = self.program_counter.wrapping_add(1);
         let value_low = bus.read(address as u16);
         let value_high = bus.read(address.wrapping_add(1) as u16);
-        ((value_high as u16) << 8 | value_low as u16).wrapping_add(self.y as u16)
+        (value_high as u16) << 8 | value_low as u16
+    }
+
+    fn read_indirect_addressed_y_indexed(&mut self, bus: &mut Bus, is_write: bool) -> (u16, bool) {
+        let base = self.read_zero_page_pointer(bus);
+        self.index_address(bus, base, self.y, is_write)
+    }
+
+    // Single byte instructions still fetch the byte after the opcode and discard it
+    fn dummy_read(&mut self, bus: &mut Bus) {
+        bus.read(self.program_counter);
     }
 
-    fn shift_left(&mut self, bus: &mut Bus, address: u16) {
+    fn shift_left(&mut self, bus: &mut Bus, address: u16) -> u8 {
         let mut value = bus.read(address);
+        // Read-modify-write instructions write the unmodified value back before the result
+        bus.write(address, value);
         self.flag_carry = value & 0x80 != 0;
         value <<= 1;
         self.flag_zero = value == 0;
         self.flag_negative = value & 0x80 != 0;
         bus.write(address, value);
+        value
     }
 
-    fn shift_right(&mut self, bus: &mut Bus, address: u16) {
+    fn shift_right(&mut self, bus: &mut Bus, address: u16) -> u8 {
         let mut value = bus.read(address);
+        bus.write(address, value);
         self.flag_carry = value & 1 != 0;
         value >>= 1;
         self.flag_zero = value == 0;
         self.flag_negative = value & 0x80 != 0;
         bus.write(address, value);
+        value
     }
 
-    fn rotate_left(&mut self, bus: &mut Bus, address: u16) {
+    fn rotate_left(&mut self, bus: &mut Bus, address: u16) -> u8 {
         let mut value = bus.read(address);
+        bus.write(address, value);
         let old_carry = self.flag_carry;
         self.flag_carry = value & 0x80 != 0;
         value <<= 1;
@@ -240,10 +341,12 @@ impl Cpu {
         self.flag_zero = value == 0;
         self.flag_negative = value & 0x80 != 0;
         bus.write(address, value);
+        value
     }
 
-    fn rotate_right(&mut self, bus: &mut Bus, address: u16) {
+    fn rotate_right(&mut self, bus: &mut Bus, address: u16) -> u8 {
         let mut value = bus.read(address);
+        bus.write(address, value);
         let old_carry = self.flag_carry;
         self.flag_carry = value & 1 != 0;
         value >>= 1;
@@ -253,22 +356,97 @@ impl Cpu {
         self.flag_zero = value == 0;
         self.flag_negative = value & 0x80 != 0;
         bus.write(address, value);
+        value
     }
 
-    fn increment(&mut self, bus: &mut Bus, address: u16) {
+    fn increment(&mut self, bus: &mut Bus, address: u16) -> u8 {
         let mut value = bus.read(address);
+        bus.write(address, value);
         value = value.wrapping_add(1);
         self.flag_zero = value == 0;
         self.flag_negative = value & 0x80 != 0;
         bus.write(address, value);
+        value
     }
 
-    fn decrement(&mut self, bus: &mut Bus, address: u16) {
+    fn decrement(&mut self, bus: &mut Bus, address: u16) -> u8 {
         let mut value = bus.read(address);
+        bus.write(address, value);
         value = value.wrapping_sub(1);
         self.flag_zero = value == 0;
         self.flag_negative = value & 0x80 != 0;
         bus.write(address, value);
+        value
+    }
+
+    fn shift_left_or(&mut self, bus: &mut Bus, address: u16) {
+        let value = self.shift_left(bus, address);
+        self.a |= value;
+        self.flag_zero = self.a == 0;
+        self.flag_negative = self.a & 0x80 != 0;
+    }
+
+    fn rotate_left_and(&mut self, bus: &mut Bus, address: u16) {
+        let value = self.rotate_left(bus, address);
+        self.a &= value;
+        self.flag_zero = self.a == 0;
+        self.flag_negative = self.a & 0x80 != 0;
+    }
+
+    fn shift_right_eor(&mut self, bus: &mut Bus, address: u16) {
+        let value = self.shift_right(bus, address);
+        self.a ^= value;
+        self.flag_zero = self.a == 0;
+        self.flag_negative = self.a & 0x80 != 0;
+    }
+
+    fn rotate_right_add(&mut self, bus: &mut Bus, address: u16) {
+        let value = self.rotate_right(bus, address);
+        self.add_carry(value);
+    }
+
+    fn decrement_compare(&mut self, bus: &mut Bus, address: u16) {
+        let value = self.decrement(bus, address);
+        self.compare_a(value);
+    }
+
+    fn increment_subtract(&mut self, bus: &mut Bus, address: u16) {
+        let value = self.increment(bus, address);
+        self.sub_carry(value);
+    }
+
+    fn load_a_x(&mut self, value: u8) {
+        self.a = value;
+        self.x = value;
+        self.flag_zero = value == 0;
+        self.flag_negative = value & 0x80 != 0;
+    }
+
+    fn and_carry(&mut self, value: u8) {
+        self.a &= value;
+        self.flag_zero = self.a == 0;
+        self.flag_negative = self.a & 0x80 != 0;
+        self.flag_carry = self.flag_negative;
+    }
+
+    fn and_rotate_right(&mut self, value: u8) {
+        self.a &= value;
+        self.a = (self.a >> 1) | (self.flag_carry as u8) << 7;
+        self.flag_zero = self.a == 0;
+        self.flag_negative = self.a & 0x80 != 0;
+        self.flag_carry = self.a & 0x40 != 0;
+        self.flag_overflow = ((self.a >> 6) ^ (self.a >> 5)) & 1 != 0;
+    }
+
+    // SHA, SHX, SHY and SHS store the value ANDed with the high byte of the base address plus
+    // one, and if indexing crosses a page that value also replaces the high byte of the address
+    fn store_high_byte_and(&mut self, bus: &mut Bus, base: u16, index: u8, value: u8) {
+        let (mut address, page_crossed) = self.index_address(bus, base, index, true);
+        let value = value & ((base >> 8) as u8).wrapping_add(1);
+        if page_crossed {
+            address = (value as u16) << 8 | (address & 0x00FF);
+        }
+        bus.write(address, value);
     }
 
     fn bitwise_or(&mut self, bus: &mut Bus, address: u16) {
@@ -334,13 +512,15 @@ impl Cpu {
         self.flag_overflow = value & 0x40 != 0;
     }
 
-    fn branch(&mut self, condition: bool, offset: u8) -> u8 {
+    fn branch(&mut self, bus: &mut Bus, condition: bool, offset: u8) -> u8 {
         if condition {
+            self.dummy_read(bus);
             let signed_offset = offset as i8 as i16;
             let new_program_counter = self.program_counter.wrapping_add(signed_offset as u16);
             let cycles = if new_program_counter & 0xFF00 == self.program_counter & 0xFF00 {
                 3
             } else {
+                bus.read((self.program_counter & 0xFF00) | (new_program_counter & 0x00FF));
                 4
             };
             self.program_counter = new_program_counter;
@@ -350,27 +530,24 @@ impl Cpu {
         }
     }
 
-    pub fn emulate_cpu(&mut self, bus: &mut Bus) -> u8 {
+    pub fn emulate_cpu(&mut self, bus: &mut Bus) -> Result<u16, NesError> {
+        if self.nmi_pending || self.irq_pending {
+            // The interrupt sequence fetches the next opcode twice and discards it
+            self.dummy_read(bus);
+            self.dummy_read(bus);
+            let cycles = self.interrupt(bus, IRQ_VECTOR, false);
+            return self.check_fault(bus, cycles as u16);
+        }
+
         let opcode = bus.read(self.program_counter);
         self.program_counter = self.program_counter.wrapping_add(1);
+        let interrupt_disable = self.flag_interrupt_disable;
 
-        match opcode {
+        let cycles = match opcode {
             0x00 => {
                 // BRK
-                self.program_counter = self.program_counter.wrapping_add(1);
-                self.push(bus, (self.program_counter >> 8) as u8);
-                self.push(bus, self.program_counter as u8);
-
-                let status = self.get_status_register(true);
-                self.push(bus, status);
-
-                self.flag_interrupt_disable = true;
-
-                let destination_address_low = bus.read(0xFFFE);
-                let destination_address_high = bus.read(0xFFFF);
-                self.program_counter =
-                    (destination_address_high as u16) << 8 | destination_address_low as u16;
-                7
+                self.read_immediate_addressed(bus);
+                self.interrupt(bus, IRQ_VECTOR, true)
             }
             0x01 => {
                 // ORA Indirect,X
@@ -378,11 +555,29 @@ impl Cpu {
                 self.bitwise_or(bus, address);
                 6
             }
-            0x02 => {
-                // HTL
+            0x02 | 0x12 | 0x22 | 0x32 | 0x42 | 0x52 | 0x62 | 0x72 | 0x92 | 0xB2 | 0xD2 | 0xF2 => {
+                // HLT
                 self.halted = true;
+                if self.jam_policy == JamPolicy::Error {
+                    return Err(NesError::CpuJam {
+                        opcode,
+                        address: self.program_counter.wrapping_sub(1),
+                    });
+                }
                 0
             }
+            0x03 => {
+                // SLO Indirect,X
+                let address = self.read_indirect_addressed_x_indexed(bus);
+                self.shift_left_or(bus, address);
+                8
+            }
+            0x04 => {
+                // NOP Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                bus.read(address);
+                3
+            }
             0x05 => {
                 // ORA Zero Page
                 let address = self.read_immediate_addressed(bus);
@@ -395,8 +590,15 @@ impl Cpu {
                 self.shift_left(bus, address as u16);
                 5
             }
+            0x07 => {
+                // SLO Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                self.shift_left_or(bus, address);
+                5
+            }
             0x08 => {
                 // PHP
+                self.dummy_read(bus);
                 let status = self.get_status_register(true);
                 self.push(bus, status);
                 3
@@ -411,12 +613,25 @@ impl Cpu {
             }
             0x0A => {
                 // ASL A
+                self.dummy_read(bus);
                 self.flag_carry = self.a & 0x80 != 0;
                 self.a <<= 1;
                 self.flag_zero = self.a == 0;
                 self.flag_negative = self.a & 0x80 != 0;
                 2
             }
+            0x0B => {
+                // ANC Immediate
+                let value = self.read_immediate_addressed(bus);
+                self.and_carry(value);
+                2
+            }
+            0x0C => {
+                // NOP Absolute
+                let address = self.read_absolute_addressed(bus);
+                bus.read(address);
+                4
+            }
             0x0D => {
                 // ORA Absolute
                 let address = self.read_absolute_addressed(bus);
@@ -429,16 +644,34 @@ impl Cpu {
                 self.shift_left(bus, address);
                 6
             }
+            0x0F => {
+                // SLO Absolute
+                let address = self.read_absolute_addressed(bus);
+                self.shift_left_or(bus, address);
+                6
+            }
             0x10 => {
                 // BPL
                 let offset = self.read_immediate_addressed(bus);
-                self.branch(!self.flag_negative, offset)
+                self.branch(bus, !self.flag_negative, offset)
             }
             0x11 => {
                 // ORA Indirect,Y
-                let address = self.read_indirect_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_indirect_addressed_y_indexed(bus, false);
                 self.bitwise_or(bus, address);
-                5
+                5 + page_crossed as u8
+            }
+            0x13 => {
+                // SLO Indirect,Y
+                let (address, _) = self.read_indirect_addressed_y_indexed(bus, true);
+                self.shift_left_or(bus, address);
+                8
+            }
+            0x14 => {
+                // NOP Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                bus.read(address);
+                4
             }
             0x15 => {
                 // ORA Zero Page,X
@@ -452,36 +685,67 @@ impl Cpu {
                 self.shift_left(bus, address);
                 6
             }
+            0x17 => {
+                // SLO Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                self.shift_left_or(bus, address);
+                6
+            }
             0x18 => {
                 // CLC
+                self.dummy_read(bus);
                 self.flag_carry = false;
                 2
             }
             0x19 => {
                 // ORA Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
                 self.bitwise_or(bus, address);
-                4
+                4 + page_crossed as u8
+            }
+            0x1A | 0x3A | 0x5A | 0x7A | 0xDA | 0xFA => {
+                // NOP (unofficial)
+                self.dummy_read(bus);
+                2
+            }
+            0x1B => {
+                // SLO Absolute,Y
+                let (address, _) = self.read_absolute_addressed_y_indexed(bus, true);
+                self.shift_left_or(bus, address);
+                7
+            }
+            0x1C => {
+                // NOP Absolute,X
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
+                bus.read(address);
+                4 + page_crossed as u8
             }
             0x1D => {
                 // ORA Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
                 self.bitwise_or(bus, address);
-                4
+                4 + page_crossed as u8
             }
             0x1E => {
                 // ASL Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
                 self.shift_left(bus, address);
                 7
             }
+            0x1F => {
+                // SLO Absolute,X
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
+                self.shift_left_or(bus, address);
+                7
+            }
             0x20 => {
                 // JSR
                 let destination_address_low = bus.read(self.program_counter);
                 self.program_counter = self.program_counter.wrapping_add(1);
-                let destination_address_high = bus.read(self.program_counter);
+                bus.read(0x100 + self.stack_pointer as u16);
                 self.push(bus, (self.program_counter >> 8) as u8);
                 self.push(bus, self.program_counter as u8);
+                let destination_address_high = bus.read(self.program_counter);
                 self.program_counter =
                     (destination_address_high as u16) << 8 | destination_address_low as u16;
                 6
@@ -492,6 +756,12 @@ impl Cpu {
                 self.bitwise_and(bus, address);
                 6
             }
+            0x23 => {
+                // RLA Indirect,X
+                let address = self.read_indirect_addressed_x_indexed(bus);
+                self.rotate_left_and(bus, address);
+                8
+            }
             0x24 => {
                 // BIT Zero Page
                 let address = self.read_immediate_addressed(bus);
@@ -511,8 +781,16 @@ impl Cpu {
                 self.rotate_left(bus, address as u16);
                 5
             }
+            0x27 => {
+                // RLA Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                self.rotate_left_and(bus, address);
+                5
+            }
             0x28 => {
                 // PLP
+                self.dummy_read(bus);
+                bus.read(0x100 + self.stack_pointer as u16);
                 let status = self.pull(bus);
                 self.set_status_register(status);
                 4
@@ -527,6 +805,7 @@ impl Cpu {
             }
             0x2A => {
                 // ROL A
+                self.dummy_read(bus);
                 let old_carry = self.flag_carry;
                 self.flag_carry = self.a & 0x80 != 0;
                 self.a <<= 1;
@@ -537,6 +816,12 @@ impl Cpu {
                 self.flag_negative = self.a & 0x80 != 0;
                 2
             }
+            0x2B => {
+                // ANC Immediate
+                let value = self.read_immediate_addressed(bus);
+                self.and_carry(value);
+                2
+            }
             0x2C => {
                 // BIT Absolute
                 let address = self.read_absolute_addressed(bus);
@@ -556,16 +841,34 @@ impl Cpu {
                 self.rotate_left(bus, address);
                 6
             }
+            0x2F => {
+                // RLA Absolute
+                let address = self.read_absolute_addressed(bus);
+                self.rotate_left_and(bus, address);
+                6
+            }
             0x30 => {
                 // BMI
                 let offset = self.read_immediate_addressed(bus);
-                self.branch(self.flag_negative, offset)
+                self.branch(bus, self.flag_negative, offset)
             }
             0x31 => {
                 // AND Indirect,Y
-                let address = self.read_indirect_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_indirect_addressed_y_indexed(bus, false);
                 self.bitwise_and(bus, address);
-                5
+                5 + page_crossed as u8
+            }
+            0x33 => {
+                // RLA Indirect,Y
+                let (address, _) = self.read_indirect_addressed_y_indexed(bus, true);
+                self.rotate_left_and(bus, address);
+                8
+            }
+            0x34 => {
+                // NOP Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                bus.read(address);
+                4
             }
             0x35 => {
                 // AND Zero Page,X
@@ -579,31 +882,58 @@ impl Cpu {
                 self.rotate_left(bus, address);
                 6
             }
+            0x37 => {
+                // RLA Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                self.rotate_left_and(bus, address);
+                6
+            }
             0x38 => {
                 // SEC
+                self.dummy_read(bus);
                 self.flag_carry = true;
                 2
             }
             0x39 => {
                 // AND Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
                 self.bitwise_and(bus, address);
-                4
+                4 + page_crossed as u8
+            }
+            0x3B => {
+                // RLA Absolute,Y
+                let (address, _) = self.read_absolute_addressed_y_indexed(bus, true);
+                self.rotate_left_and(bus, address);
+                7
+            }
+            0x3C => {
+                // NOP Absolute,X
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
+                bus.read(address);
+                4 + page_crossed as u8
             }
             0x3D => {
                 // AND Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
                 self.bitwise_and(bus, address);
-                4
+                4 + page_crossed as u8
             }
             0x3E => {
                 // ROL Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
                 self.rotate_left(bus, address);
                 7
             }
+            0x3F => {
+                // RLA Absolute,X
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
+                self.rotate_left_and(bus, address);
+                7
+            }
             0x40 => {
                 // RTI
+                self.dummy_read(bus);
+                bus.read(0x100 + self.stack_pointer as u16);
                 let status = self.pull(bus);
                 self.set_status_register(status);
 
@@ -619,6 +949,18 @@ impl Cpu {
                 self.bitwise_eor(bus, address);
                 6
             }
+            0x43 => {
+                // SRE Indirect,X
+                let address = self.read_indirect_addressed_x_indexed(bus);
+                self.shift_right_eor(bus, address);
+                8
+            }
+            0x44 => {
+                // NOP Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                bus.read(address);
+                3
+            }
             0x45 => {
                 // EOR Zero Page
                 let address = self.read_immediate_addressed(bus);
@@ -631,8 +973,15 @@ impl Cpu {
                 self.shift_right(bus, address as u16);
                 5
             }
+            0x47 => {
+                // SRE Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                self.shift_right_eor(bus, address);
+                5
+            }
             0x48 => {
                 // PHA
+                self.dummy_read(bus);
                 self.push(bus, self.a);
                 3
             }
@@ -646,12 +995,23 @@ impl Cpu {
             }
             0x4A => {
                 // LSR A
+                self.dummy_read(bus);
                 self.flag_carry = self.a & 1 != 0;
                 self.a >>= 1;
                 self.flag_zero = self.a == 0;
                 self.flag_negative = self.a & 0x80 != 0;
                 2
             }
+            0x4B => {
+                // ALR Immediate
+                let value = self.read_immediate_addressed(bus);
+                self.a &= value;
+                self.flag_carry = self.a & 1 != 0;
+                self.a >>= 1;
+                self.flag_zero = self.a == 0;
+                self.flag_negative = false;
+                2
+            }
             0x4C => {
                 // JMP
                 self.program_counter = self.read_absolute_addressed(bus);
@@ -669,16 +1029,34 @@ impl Cpu {
                 self.shift_right(bus, address);
                 6
             }
+            0x4F => {
+                // SRE Absolute
+                let address = self.read_absolute_addressed(bus);
+                self.shift_right_eor(bus, address);
+                6
+            }
             0x50 => {
                 // BVC
                 let offset = self.read_immediate_addressed(bus);
-                self.branch(!self.flag_overflow, offset)
+                self.branch(bus, !self.flag_overflow, offset)
             }
             0x51 => {
                 // EOR Indirect,Y
-                let address = self.read_indirect_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_indirect_addressed_y_indexed(bus, false);
                 self.bitwise_eor(bus, address);
-                5
+                5 + page_crossed as u8
+            }
+            0x53 => {
+                // SRE Indirect,Y
+                let (address, _) = self.read_indirect_addressed_y_indexed(bus, true);
+                self.shift_right_eor(bus, address);
+                8
+            }
+            0x54 => {
+                // NOP Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                bus.read(address);
+                4
             }
             0x55 => {
                 // EOR Zero Page,X
@@ -692,35 +1070,63 @@ impl Cpu {
                 self.shift_right(bus, address);
                 6
             }
+            0x57 => {
+                // SRE Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                self.shift_right_eor(bus, address);
+                6
+            }
             0x58 => {
                 // CLI
+                self.dummy_read(bus);
                 self.flag_interrupt_disable = false;
                 2
             }
             0x59 => {
                 // EOR Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
                 self.bitwise_eor(bus, address);
-                4
+                4 + page_crossed as u8
+            }
+            0x5B => {
+                // SRE Absolute,Y
+                let (address, _) = self.read_absolute_addressed_y_indexed(bus, true);
+                self.shift_right_eor(bus, address);
+                7
+            }
+            0x5C => {
+                // NOP Absolute,X
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
+                bus.read(address);
+                4 + page_crossed as u8
             }
             0x5D => {
                 // EOR Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
                 self.bitwise_eor(bus, address);
-                4
+                4 + page_crossed as u8
             }
             0x5E => {
                 // LSR Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
                 self.shift_right(bus, address);
                 7
             }
+            0x5F => {
+                // SRE Absolute,X
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
+                self.shift_right_eor(bus, address);
+                7
+            }
             0x60 => {
                 // RTS
+                self.dummy_read(bus);
+                bus.read(0x100 + self.stack_pointer as u16);
                 let return_address_low = self.pull(bus);
                 let return_address_high = self.pull(bus);
                 self.program_counter =
                     (return_address_high as u16) << 8 | return_address_low as u16;
+                self.dummy_read(bus);
                 self.program_counter = self.program_counter.wrapping_add(1);
                 6
             }
@@ -731,6 +1137,18 @@ impl Cpu {
                 self.add_carry(value);
                 6
             }
+            0x63 => {
+                // RRA Indirect,X
+                let address = self.read_indirect_addressed_x_indexed(bus);
+                self.rotate_right_add(bus, address);
+                8
+            }
+            0x64 => {
+                // NOP Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                bus.read(address);
+                3
+            }
             0x65 => {
                 // ADC Zero Page
                 let address = self.read_immediate_addressed(bus);
@@ -744,8 +1162,16 @@ impl Cpu {
                 self.rotate_right(bus, address as u16);
                 5
             }
+            0x67 => {
+                // RRA Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                self.rotate_right_add(bus, address);
+                5
+            }
             0x68 => {
                 // PLA
+                self.dummy_read(bus);
+                bus.read(0x100 + self.stack_pointer as u16);
                 self.a = self.pull(bus);
                 self.flag_zero = self.a == 0;
                 self.flag_negative = self.a & 0x80 != 0;
@@ -759,6 +1185,7 @@ impl Cpu {
             }
             0x6A => {
                 // ROR A
+                self.dummy_read(bus);
                 let old_carry = self.flag_carry;
                 self.flag_carry = self.a & 1 != 0;
                 self.a >>= 1;
@@ -769,6 +1196,12 @@ impl Cpu {
                 self.flag_negative = self.a & 0x80 != 0;
                 2
             }
+            0x6B => {
+                // ARR Immediate
+                let value = self.read_immediate_addressed(bus);
+                self.and_rotate_right(value);
+                2
+            }
             0x6C => {
                 // JMP Indirect
                 let value = self.read_indirect_addressed(bus);
@@ -788,17 +1221,35 @@ impl Cpu {
                 self.rotate_right(bus, address);
                 6
             }
+            0x6F => {
+                // RRA Absolute
+                let address = self.read_absolute_addressed(bus);
+                self.rotate_right_add(bus, address);
+                6
+            }
             0x70 => {
                 // BVS
                 let offset = self.read_immediate_addressed(bus);
-                self.branch(self.flag_overflow, offset)
+                self.branch(bus, self.flag_overflow, offset)
             }
             0x71 => {
                 // ADC Indirect,Y
-                let address = self.read_indirect_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_indirect_addressed_y_indexed(bus, false);
                 let value = bus.read(address);
                 self.add_carry(value);
-                5
+                5 + page_crossed as u8
+            }
+            0x73 => {
+                // RRA Indirect,Y
+                let (address, _) = self.read_indirect_addressed_y_indexed(bus, true);
+                self.rotate_right_add(bus, address);
+                8
+            }
+            0x74 => {
+                // NOP Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                bus.read(address);
+                4
             }
             0x75 => {
                 // ADC Zero Page,X
@@ -813,37 +1264,73 @@ impl Cpu {
                 self.rotate_right(bus, address);
                 6
             }
+            0x77 => {
+                // RRA Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                self.rotate_right_add(bus, address);
+                6
+            }
             0x78 => {
                 // SEI
+                self.dummy_read(bus);
                 self.flag_interrupt_disable = true;
                 2
             }
             0x79 => {
                 // ADC Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
                 let value = bus.read(address);
                 self.add_carry(value);
-                4
+                4 + page_crossed as u8
+            }
+            0x7B => {
+                // RRA Absolute,Y
+                let (address, _) = self.read_absolute_addressed_y_indexed(bus, true);
+                self.rotate_right_add(bus, address);
+                7
+            }
+            0x7C => {
+                // NOP Absolute,X
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
+                bus.read(address);
+                4 + page_crossed as u8
             }
             0x7D => {
                 // ADC Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
                 let value = bus.read(address);
                 self.add_carry(value);
-                4
+                4 + page_crossed as u8
             }
             0x7E => {
                 // ROR Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
                 self.rotate_right(bus, address);
                 7
             }
+            0x7F => {
+                // RRA Absolute,X
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
+                self.rotate_right_add(bus, address);
+                7
+            }
+            0x80 | 0x82 | 0x89 | 0xC2 | 0xE2 => {
+                // NOP Immediate
+                self.read_immediate_addressed(bus);
+                2
+            }
             0x81 => {
                 // STA Indirect,X
                 let address = self.read_indirect_addressed_x_indexed(bus);
                 bus.write(address, self.a);
                 6
             }
+            0x83 => {
+                // SAX Indirect,X
+                let address = self.read_indirect_addressed_x_indexed(bus);
+                bus.write(address, self.a & self.x);
+                6
+            }
             0x84 => {
                 // STY Zero Page
                 let destination_address = self.read_immediate_addressed(bus);
@@ -862,8 +1349,15 @@ impl Cpu {
                 bus.write(destination_address as u16, self.x);
                 3
             }
+            0x87 => {
+                // SAX Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                bus.write(address, self.a & self.x);
+                3
+            }
             0x88 => {
                 // DEY
+                self.dummy_read(bus);
                 self.y = self.y.wrapping_sub(1);
                 self.flag_zero = self.y == 0;
                 self.flag_negative = self.y & 0x80 != 0;
@@ -871,11 +1365,20 @@ impl Cpu {
             }
             0x8A => {
                 // TXA
+                self.dummy_read(bus);
                 self.a = self.x;
                 self.flag_zero = self.a == 0;
                 self.flag_negative = self.a & 0x80 != 0;
                 2
             }
+            0x8B => {
+                // ANE Immediate (unstable, uses the common 0xEE magic constant)
+                let value = self.read_immediate_addressed(bus);
+                self.a = (self.a | 0xEE) & self.x & value;
+                self.flag_zero = self.a == 0;
+                self.flag_negative = self.a & 0x80 != 0;
+                2
+            }
             0x8C => {
                 // STY Absolute
                 let destination_address = self.read_absolute_addressed(bus);
@@ -894,17 +1397,29 @@ impl Cpu {
                 bus.write(destination_address, self.x);
                 4
             }
+            0x8F => {
+                // SAX Absolute
+                let address = self.read_absolute_addressed(bus);
+                bus.write(address, self.a & self.x);
+                4
+            }
             0x90 => {
                 // BCC
                 let offset = self.read_immediate_addressed(bus);
-                self.branch(!self.flag_carry, offset)
+                self.branch(bus, !self.flag_carry, offset)
             }
             0x91 => {
                 // STA Indirect,Y
-                let address = self.read_indirect_addressed_y_indexed(bus);
+                let (address, _) = self.read_indirect_addressed_y_indexed(bus, true);
                 bus.write(address, self.a);
                 6
             }
+            0x93 => {
+                // SHA Indirect,Y
+                let base = self.read_zero_page_pointer(bus);
+                self.store_high_byte_and(bus, base, self.y, self.a & self.x);
+                6
+            }
             0x94 => {
                 // STY Zero Page,X
                 let address = self.read_zero_page_addressed_x_indexed(bus);
@@ -923,8 +1438,15 @@ impl Cpu {
                 bus.write(address, self.x);
                 4
             }
+            0x97 => {
+                // SAX Zero Page,Y
+                let address = self.read_zero_page_addressed_y_indexed(bus);
+                bus.write(address, self.a & self.x);
+                4
+            }
             0x98 => {
                 // TYA
+                self.dummy_read(bus);
                 self.a = self.y;
                 self.flag_zero = self.a == 0;
                 self.flag_negative = self.a & 0x80 != 0;
@@ -932,21 +1454,47 @@ impl Cpu {
             }
             0x99 => {
                 // STA Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, _) = self.read_absolute_addressed_y_indexed(bus, true);
                 bus.write(address, self.a);
                 5
             }
             0x9A => {
                 // TXS
+                self.dummy_read(bus);
                 self.stack_pointer = self.x;
                 2
             }
+            0x9B => {
+                // SHS Absolute,Y
+                let base = self.read_absolute_addressed(bus);
+                self.stack_pointer = self.a & self.x;
+                self.store_high_byte_and(bus, base, self.y, self.stack_pointer);
+                5
+            }
+            0x9C => {
+                // SHY Absolute,X
+                let base = self.read_absolute_addressed(bus);
+                self.store_high_byte_and(bus, base, self.x, self.y);
+                5
+            }
             0x9D => {
                 // STA Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
                 bus.write(address, self.a);
                 5
             }
+            0x9E => {
+                // SHX Absolute,Y
+                let base = self.read_absolute_addressed(bus);
+                self.store_high_byte_and(bus, base, self.y, self.x);
+                5
+            }
+            0x9F => {
+                // SHA Absolute,Y
+                let base = self.read_absolute_addressed(bus);
+                self.store_high_byte_and(bus, base, self.y, self.a & self.x);
+                5
+            }
             0xA0 => {
                 // LDY Immediate
                 self.y = self.read_immediate_addressed(bus);
@@ -969,6 +1517,13 @@ impl Cpu {
                 self.flag_negative = self.x & 0x80 != 0;
                 2
             }
+            0xA3 => {
+                // LAX Indirect,X
+                let address = self.read_indirect_addressed_x_indexed(bus);
+                let value = bus.read(address);
+                self.load_a_x(value);
+                6
+            }
             0xA4 => {
                 // LDY Zero Page
                 let address = self.read_immediate_addressed(bus);
@@ -993,8 +1548,16 @@ impl Cpu {
                 self.flag_negative = self.x & 0x80 != 0;
                 3
             }
+            0xA7 => {
+                // LAX Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                let value = bus.read(address);
+                self.load_a_x(value);
+                3
+            }
             0xA8 => {
                 // TAY
+                self.dummy_read(bus);
                 self.y = self.a;
                 self.flag_zero = self.y == 0;
                 self.flag_negative = self.y & 0x80 != 0;
@@ -1009,11 +1572,18 @@ impl Cpu {
             }
             0xAA => {
                 // TAX
+                self.dummy_read(bus);
                 self.x = self.a;
                 self.flag_zero = self.x == 0;
                 self.flag_negative = self.x & 0x80 != 0;
                 2
             }
+            0xAB => {
+                // LXA Immediate (unstable, uses the common 0xEE magic constant)
+                let value = self.read_immediate_addressed(bus);
+                self.load_a_x((self.a | 0xEE) & value);
+                2
+            }
             0xAC => {
                 // LDY Absolute
                 let destination_address = self.read_absolute_addressed(bus);
@@ -1038,18 +1608,32 @@ impl Cpu {
                 self.flag_negative = self.x & 0x80 != 0;
                 4
             }
+            0xAF => {
+                // LAX Absolute
+                let address = self.read_absolute_addressed(bus);
+                let value = bus.read(address);
+                self.load_a_x(value);
+                4
+            }
             0xB0 => {
                 // BCS
                 let offset = self.read_immediate_addressed(bus);
-                self.branch(self.flag_carry, offset)
+                self.branch(bus, self.flag_carry, offset)
             }
             0xB1 => {
                 // LDA Indirect,Y
-                let address = self.read_indirect_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_indirect_addressed_y_indexed(bus, false);
                 self.a = bus.read(address);
                 self.flag_zero = self.a == 0;
                 self.flag_negative = self.a & 0x80 != 0;
-                5
+                5 + page_crossed as u8
+            }
+            0xB3 => {
+                // LAX Indirect,Y
+                let (address, page_crossed) = self.read_indirect_addressed_y_indexed(bus, false);
+                let value = bus.read(address);
+                self.load_a_x(value);
+                5 + page_crossed as u8
             }
             0xB4 => {
                 // LDY Zero Page,X
@@ -1075,49 +1659,73 @@ impl Cpu {
                 self.flag_negative = self.x & 0x80 != 0;
                 4
             }
+            0xB7 => {
+                // LAX Zero Page,Y
+                let address = self.read_zero_page_addressed_y_indexed(bus);
+                let value = bus.read(address);
+                self.load_a_x(value);
+                4
+            }
             0xB8 => {
                 // CLV
+                self.dummy_read(bus);
                 self.flag_overflow = false;
                 2
             }
             0xB9 => {
                 // LDA Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
                 self.a = bus.read(address);
                 self.flag_zero = self.a == 0;
                 self.flag_negative = self.a & 0x80 != 0;
-                4
+                4 + page_crossed as u8
             }
             0xBA => {
                 // TSX
+                self.dummy_read(bus);
                 self.x = self.stack_pointer;
                 self.flag_zero = self.x == 0;
                 self.flag_negative = self.x & 0x80 != 0;
                 2
             }
+            0xBB => {
+                // LAE Absolute,Y
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
+                let value = bus.read(address) & self.stack_pointer;
+                self.stack_pointer = value;
+                self.load_a_x(value);
+                4 + page_crossed as u8
+            }
             0xBC => {
                 // LDY Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
                 self.y = bus.read(address);
                 self.flag_zero = self.y == 0;
                 self.flag_negative = self.y & 0x80 != 0;
-                4
+                4 + page_crossed as u8
             }
             0xBD => {
                 // LDA Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
                 self.a = bus.read(address);
                 self.flag_zero = self.a == 0;
                 self.flag_negative = self.a & 0x80 != 0;
-                4
+                4 + page_crossed as u8
             }
             0xBE => {
                 // LDX Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
                 self.x = bus.read(address);
                 self.flag_zero = self.x == 0;
                 self.flag_negative = self.x & 0x80 != 0;
-                4
+                4 + page_crossed as u8
+            }
+            0xBF => {
+                // LAX Absolute,Y
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
+                let value = bus.read(address);
+                self.load_a_x(value);
+                4 + page_crossed as u8
             }
             0xC0 => {
                 // CPY Immediate
@@ -1132,6 +1740,12 @@ impl Cpu {
                 self.compare_a(value);
                 6
             }
+            0xC3 => {
+                // DCP Indirect,X
+                let address = self.read_indirect_addressed_x_indexed(bus);
+                self.decrement_compare(bus, address);
+                8
+            }
             0xC4 => {
                 // CPY Zero Page
                 let address = self.read_immediate_addressed(bus);
@@ -1152,8 +1766,15 @@ impl Cpu {
                 self.decrement(bus, address as u16);
                 5
             }
+            0xC7 => {
+                // DCP Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                self.decrement_compare(bus, address);
+                5
+            }
             0xC8 => {
                 // INY
+                self.dummy_read(bus);
                 self.y = self.y.wrapping_add(1);
                 self.flag_zero = self.y == 0;
                 self.flag_negative = self.y & 0x80 != 0;
@@ -1167,11 +1788,22 @@ impl Cpu {
             }
             0xCA => {
                 // DEX
+                self.dummy_read(bus);
                 self.x = self.x.wrapping_sub(1);
                 self.flag_zero = self.x == 0;
                 self.flag_negative = self.x & 0x80 != 0;
                 2
             }
+            0xCB => {
+                // AXS Immediate
+                let value = self.read_immediate_addressed(bus);
+                let and_result = self.a & self.x;
+                self.flag_carry = and_result >= value;
+                self.x = and_result.wrapping_sub(value);
+                self.flag_zero = self.x == 0;
+                self.flag_negative = self.x & 0x80 != 0;
+                2
+            }
             0xCC => {
                 // CPY Absolute
                 let address = self.read_absolute_addressed(bus);
@@ -1192,17 +1824,35 @@ impl Cpu {
                 self.decrement(bus, address);
                 6
             }
+            0xCF => {
+                // DCP Absolute
+                let address = self.read_absolute_addressed(bus);
+                self.decrement_compare(bus, address);
+                6
+            }
             0xD0 => {
                 // BNE
                 let offset = self.read_immediate_addressed(bus);
-                self.branch(!self.flag_zero, offset)
+                self.branch(bus, !self.flag_zero, offset)
             }
             0xD1 => {
                 // CMP Indirect,Y
-                let address = self.read_indirect_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_indirect_addressed_y_indexed(bus, false);
                 let value = bus.read(address);
                 self.compare_a(value);
-                5
+                5 + page_crossed as u8
+            }
+            0xD3 => {
+                // DCP Indirect,Y
+                let (address, _) = self.read_indirect_addressed_y_indexed(bus, true);
+                self.decrement_compare(bus, address);
+                8
+            }
+            0xD4 => {
+                // NOP Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                bus.read(address);
+                4
             }
             0xD5 => {
                 // CMP Zero Page,X
@@ -1217,31 +1867,56 @@ impl Cpu {
                 self.decrement(bus, address);
                 6
             }
+            0xD7 => {
+                // DCP Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                self.decrement_compare(bus, address);
+                6
+            }
             0xD8 => {
                 // CLD
+                self.dummy_read(bus);
                 self.flag_decimal = false;
                 2
             }
             0xD9 => {
                 // CMP Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
                 let value = bus.read(address);
                 self.compare_a(value);
-                4
+                4 + page_crossed as u8
+            }
+            0xDB => {
+                // DCP Absolute,Y
+                let (address, _) = self.read_absolute_addressed_y_indexed(bus, true);
+                self.decrement_compare(bus, address);
+                7
+            }
+            0xDC => {
+                // NOP Absolute,X
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
+                bus.read(address);
+                4 + page_crossed as u8
             }
             0xDD => {
                 // CMP Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
                 let value = bus.read(address);
                 self.compare_a(value);
-                4
+                4 + page_crossed as u8
             }
             0xDE => {
                 // DEC Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
                 self.decrement(bus, address);
                 7
             }
+            0xDF => {
+                // DCP Absolute,X
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
+                self.decrement_compare(bus, address);
+                7
+            }
             0xE0 => {
                 // CPX Immediate
                 let value = self.read_immediate_addressed(bus);
@@ -1255,6 +1930,12 @@ impl Cpu {
                 self.sub_carry(value);
                 6
             }
+            0xE3 => {
+                // ISC Indirect,X
+                let address = self.read_indirect_addressed_x_indexed(bus);
+                self.increment_subtract(bus, address);
+                8
+            }
             0xE4 => {
                 // CPX Zero Page
                 let address = self.read_immediate_addressed(bus);
@@ -1275,8 +1956,15 @@ impl Cpu {
                 self.increment(bus, address as u16);
                 5
             }
+            0xE7 => {
+                // ISC Zero Page
+                let address = self.read_immediate_addressed(bus) as u16;
+                self.increment_subtract(bus, address);
+                5
+            }
             0xE8 => {
                 // INX
+                self.dummy_read(bus);
                 self.x = self.x.wrapping_add(1);
                 self.flag_zero = self.x == 0;
                 self.flag_negative = self.x & 0x80 != 0;
@@ -1290,6 +1978,13 @@ impl Cpu {
             }
             0xEA => {
                 // NOP
+                self.dummy_read(bus);
+                2
+            }
+            0xEB => {
+                // SBC Immediate (unofficial)
+                let value = self.read_immediate_addressed(bus);
+                self.sub_carry(value);
                 2
             }
             0xEC => {
@@ -1312,17 +2007,35 @@ impl Cpu {
                 self.increment(bus, address);
                 6
             }
+            0xEF => {
+                // ISC Absolute
+                let address = self.read_absolute_addressed(bus);
+                self.increment_subtract(bus, address);
+                6
+            }
             0xF0 => {
                 // BEQ
                 let offset = self.read_immediate_addressed(bus);
-                self.branch(self.flag_zero, offset)
+                self.branch(bus, self.flag_zero, offset)
             }
             0xF1 => {
                 // SBC Indirect,Y
-                let address = self.read_indirect_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_indirect_addressed_y_indexed(bus, false);
                 let value = bus.read(address);
                 self.sub_carry(value);
-                5
+                5 + page_crossed as u8
+            }
+            0xF3 => {
+                // ISC Indirect,Y
+                let (address, _) = self.read_indirect_addressed_y_indexed(bus, true);
+                self.increment_subtract(bus, address);
+                8
+            }
+            0xF4 => {
+                // NOP Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                bus.read(address);
+                4
             }
             0xF5 => {
                 // SBC Zero Page,X
@@ -1337,35 +2050,83 @@ impl Cpu {
                 self.increment(bus, address);
                 6
             }
+            0xF7 => {
+                // ISC Zero Page,X
+                let address = self.read_zero_page_addressed_x_indexed(bus);
+                self.increment_subtract(bus, address);
+                6
+            }
             0xF8 => {
                 // SED
+                self.dummy_read(bus);
                 self.flag_decimal = true;
                 2
             }
             0xF9 => {
                 // SBC Absolute,Y
-                let address = self.read_absolute_addressed_y_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_y_indexed(bus, false);
                 let value = bus.read(address);
                 self.sub_carry(value);
-                4
+                4 + page_crossed as u8
+            }
+            0xFB => {
+                // ISC Absolute,Y
+                let (address, _) = self.read_absolute_addressed_y_indexed(bus, true);
+                self.increment_subtract(bus, address);
+                7
+            }
+            0xFC => {
+                // NOP Absolute,X
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
+                bus.read(address);
+                4 + page_crossed as u8
             }
             0xFD => {
                 // SBC Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, page_crossed) = self.read_absolute_addressed_x_indexed(bus, false);
                 let value = bus.read(address);
                 self.sub_carry(value);
-                4
+                4 + page_crossed as u8
             }
             0xFE => {
                 // INC Absolute,X
-                let address = self.read_absolute_addressed_x_indexed(bus);
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
                 self.increment(bus, address);
                 7
             }
-            _ => {
-                // Unknown opcode
-                panic!("Unknown opcode {:02X}", opcode)
+            0xFF => {
+                // ISC Absolute,X
+                let (address, _) = self.read_absolute_addressed_x_indexed(bus, true);
+                self.increment_subtract(bus, address);
+                7
+            }
+        };
+
+        // A write to $4014 halts the CPU for the OAM DMA before the next instruction starts,
+        // which counts towards this instruction's cycles. Interrupts raised during the stall
+        // are seen by the polling below
+        let cycles = cycles as u16 + bus.run_oam_dma();
+
+        // CLI, SEI and PLP change the interrupt disable flag after polling, so their effect on
+        // IRQs is delayed by one instruction
+        match opcode {
+            0x28 | 0x58 | 0x78 => self.poll_interrupts(bus, interrupt_disable),
+            _ => self.poll_interrupts(bus, self.flag_interrupt_disable),
+        }
+
+        self.check_fault(bus, cycles)
+    }
+
+    fn check_fault(&mut self, bus: &mut Bus, cycles: u16) -> Result<u16, NesError> {
+        match bus.take_fault() {
+            Some(error) => {
+                self.halted = true;
+                match bus.unmapped_access_policy {
+                    UnmappedAccessPolicy::Error => Err(error),
+                    _ => Ok(cycles),
+                }
             }
+            None => Ok(cycles),
         }
     }
 }
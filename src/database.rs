@@ -0,0 +1,152 @@
+use crate::cartridge::Mirroring;
+use crate::error::NesError;
+use std::fmt;
+
+// A known dump, identified by the hash of its PRG ROM followed by its CHR ROM. Fields left as
+// None keep whatever the header says
+#[derive(Debug, Clone)]
+pub struct GameEntry {
+    pub crc32: u32,
+    // Lowercase hex, checked as well as the CRC32 when present
+    pub sha1: Option<String>,
+    pub name: String,
+    pub mapper: Option<u16>,
+    pub submapper: Option<u8>,
+    pub mirroring: Option<Mirroring>,
+    pub has_battery_backed_ram: Option<bool>,
+    pub prg_ram_size: Option<usize>,
+    pub chr_ram_size: Option<usize>,
+}
+
+// Header corrections for known dumps. No entries are built in, load them from a CSV file with
+// one dump per line:
+//
+// crc32,sha1,name,mapper,submapper,mirroring,battery,prg_ram_size,chr_ram_size
+//
+// The CRC32 is required and any other field may be left empty. Mirroring is one of H, V, 4, A
+// or B, battery is 0 or 1, and RAM sizes are in bytes. Blank lines and lines starting with # are
+// skipped
+#[derive(Debug, Clone, Default)]
+pub struct GameDatabase {
+    entries: Vec<GameEntry>,
+}
+
+impl GameDatabase {
+    pub fn new() -> Self {
+        GameDatabase::default()
+    }
+
+    pub fn from_csv(text: &str) -> Result<Self, NesError> {
+        let mut database = GameDatabase::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_entry(line).ok_or(NesError::InvalidDatabaseEntry(index + 1))?;
+            database.insert(entry);
+        }
+        Ok(database)
+    }
+
+    pub fn insert(&mut self, entry: GameEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn lookup(&self, crc32: u32, sha1: &str) -> Option<&GameEntry> {
+        self.entries.iter().find(|entry| {
+            entry.crc32 == crc32
+                && entry
+                    .sha1
+                    .as_ref()
+                    .is_none_or(|entry_sha1| entry_sha1 == sha1)
+        })
+    }
+}
+
+fn parse_entry(line: &str) -> Option<GameEntry> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    if fields.len() != 9 {
+        return None;
+    }
+
+    let optional = |field: &str| (!field.is_empty()).then(|| field.to_string());
+
+    Some(GameEntry {
+        crc32: u32::from_str_radix(fields[0], 16).ok()?,
+        sha1: match optional(fields[1]) {
+            Some(sha1) if sha1.len() == 40 && sha1.chars().all(|c| c.is_ascii_hexdigit()) => {
+                Some(sha1.to_ascii_lowercase())
+            }
+            Some(_) => return None,
+            None => None,
+        },
+        name: fields[2].to_string(),
+        mapper: parse_optional(fields[3])?,
+        submapper: parse_optional(fields[4])?,
+        mirroring: match fields[5] {
+            "" => None,
+            "H" => Some(Mirroring::Horizontal),
+            "V" => Some(Mirroring::Vertical),
+            "4" => Some(Mirroring::FourScreen),
+            "A" => Some(Mirroring::SingleScreenA),
+            "B" => Some(Mirroring::SingleScreenB),
+            _ => return None,
+        },
+        has_battery_backed_ram: match fields[6] {
+            "" => None,
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => return None,
+        },
+        prg_ram_size: parse_optional(fields[7])?,
+        chr_ram_size: parse_optional(fields[8])?,
+    })
+}
+
+// Empty fields parse to Some(None), bad numbers to None
+fn parse_optional<T: std::str::FromStr>(field: &str) -> Option<Option<T>> {
+    if field.is_empty() {
+        Some(None)
+    } else {
+        field.parse().ok().map(Some)
+    }
+}
+
+// A header field the database disagreed with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Correction {
+    Mapper { from: u16, to: u16 },
+    Submapper { from: u8, to: u8 },
+    Mirroring { from: Mirroring, to: Mirroring },
+    Battery { from: bool, to: bool },
+    PrgRamSize { from: usize, to: usize },
+    ChrRamSize { from: usize, to: usize },
+}
+
+impl fmt::Display for Correction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Correction::Mapper { from, to } => write!(f, "Mapper {} -> {}", from, to),
+            Correction::Submapper { from, to } => write!(f, "Submapper {} -> {}", from, to),
+            Correction::Mirroring { from, to } => {
+                write!(f, "Mirroring {:?} -> {:?}", from, to)
+            }
+            Correction::Battery { from, to } => write!(f, "Battery {} -> {}", from, to),
+            Correction::PrgRamSize { from, to } => {
+                write!(f, "PRG RAM {} bytes -> {} bytes", from, to)
+            }
+            Correction::ChrRamSize { from, to } => {
+                write!(f, "CHR RAM {} bytes -> {} bytes", from, to)
+            }
+        }
+    }
+}
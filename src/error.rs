@@ -0,0 +1,110 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NesError {
+    FileTooSmall(usize),
+    InvalidHeader,
+    TruncatedRom {
+        expected: usize,
+        actual: usize,
+    },
+    MissingPrgRom,
+    UnsupportedMapper(u16),
+    TrainerWithoutPrgRam,
+    CpuJam {
+        opcode: u8,
+        address: u16,
+    },
+    UnmappedRead(u16),
+    UnmappedWrite(u16),
+    InvalidDatabaseEntry(usize),
+    UnknownPatchFormat,
+    CorruptPatch,
+    PatchChecksumMismatch {
+        checksum: &'static str,
+        expected: u32,
+        actual: u32,
+    },
+    CorruptArchive(String),
+    NoRomInArchive,
+    ArchiveEntryNotFound(String),
+    InvalidPalette(usize),
+}
+
+impl fmt::Display for NesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NesError::FileTooSmall(size) => write!(f, "File is too small ({} bytes)", size),
+            NesError::InvalidHeader => write!(f, "Not valid iNES file"),
+            NesError::TruncatedRom { expected, actual } => write!(
+                f,
+                "ROM is truncated, header needs {} bytes but the file has {}",
+                expected, actual
+            ),
+            NesError::MissingPrgRom => write!(f, "ROM has no PRG ROM"),
+            NesError::UnsupportedMapper(mapper) => write!(f, "Unsupported mapper {}", mapper),
+            NesError::TrainerWithoutPrgRam => {
+                write!(
+                    f,
+                    "ROM has a trainer but the cartridge has no PRG RAM to load it into"
+                )
+            }
+            NesError::CpuJam { opcode, address } => {
+                write!(
+                    f,
+                    "CPU jammed on opcode {:02X} at 0x{:04X}",
+                    opcode, address
+                )
+            }
+            NesError::UnmappedRead(address) => {
+                write!(f, "Read from unmapped address 0x{:04X}", address)
+            }
+            NesError::UnmappedWrite(address) => {
+                write!(f, "Write to unmapped address 0x{:04X}", address)
+            }
+            NesError::InvalidDatabaseEntry(line) => {
+                write!(f, "Invalid game database entry on line {}", line)
+            }
+            NesError::UnknownPatchFormat => write!(f, "Patch is not an IPS, UPS or BPS file"),
+            NesError::CorruptPatch => write!(f, "Patch is corrupt or does not fit the ROM"),
+            NesError::PatchChecksumMismatch {
+                checksum,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Patch {} checksum mismatch, expected {:08X} but got {:08X}",
+                checksum, expected, actual
+            ),
+            NesError::CorruptArchive(reason) => write!(f, "Could not read archive: {}", reason),
+            NesError::NoRomInArchive => write!(f, "Archive does not contain a .nes file"),
+            NesError::ArchiveEntryNotFound(name) => {
+                write!(f, "Archive does not contain {}", name)
+            }
+            NesError::InvalidPalette(size) => write!(
+                f,
+                "Palette files must be 192 or 1536 bytes, this one is {} bytes",
+                size
+            ),
+        }
+    }
+}
+
+impl Error for NesError {}
+
+// What to do when the CPU accesses an address nothing responds to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnmappedAccessPolicy {
+    // Behave like hardware, reads return the last value seen on the data bus
+    OpenBus,
+    Halt,
+    Error,
+}
+
+// What to do when the CPU executes one of the HLT opcodes. The CPU halts either way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JamPolicy {
+    Halt,
+    Error,
+}
@@ -1,8 +1,14 @@
+pub mod archive;
 pub mod bus;
-mod cartridge;
+pub mod cartridge;
 pub mod cpu;
+pub mod database;
+pub mod error;
 pub mod joypad;
 pub mod mappers;
+pub mod nes;
 mod opcodes;
+pub mod palette;
+pub mod patch;
 pub mod ppu;
 pub mod rom;
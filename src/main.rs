@@ -1,38 +1,221 @@
 use image::ColorType::Rgb8;
-use nintendrust::bus::Bus;
-use nintendrust::cpu::Cpu;
+use nintendrust::archive::extract_rom;
+use nintendrust::database::GameDatabase;
+use nintendrust::nes::Nes;
+use nintendrust::palette::Palette;
+use nintendrust::ppu::{DebugImage, NametableView};
 use nintendrust::rom::Rom;
+use std::env;
 use std::fs;
+use std::path::Path;
 
 fn main() {
-    let file_path = "7_Graphics.nes";
-    let raw_bytes = match fs::read(file_path) {
+    // Usage: nintendrust [--entry name.nes] [--palette colors.pal] [--grid] [--viewport] [rom]
+    //     [patches...]
+    // The ROM can be a .nes file or a .zip or .gz containing one. --grid and --viewport add
+    // overlays to the nametable dump
+    let mut args: Vec<String> = env::args().collect();
+    let nametable_view = NametableView {
+        show_grid: take_flag(&mut args, "--grid"),
+        show_viewport: take_flag(&mut args, "--viewport"),
+    };
+    let Ok(entry_name) = take_option(&mut args, "--entry") else {
+        eprintln!("--entry needs the name of a file in the archive");
+        return;
+    };
+    let Ok(palette_path) = take_option(&mut args, "--palette") else {
+        eprintln!("--palette needs the path of a .pal file");
+        return;
+    };
+    let palette = match palette_path {
+        Some(path) => {
+            let data = match fs::read(&path) {
+                Ok(data) => data,
+                Err(e) => {
+                    eprintln!("Could not read palette {}: {}", path, e);
+                    return;
+                }
+            };
+            match Palette::from_pal(&data) {
+                Ok(palette) => palette,
+                Err(e) => {
+                    eprintln!("Could not load palette {}: {}", path, e);
+                    return;
+                }
+            }
+        }
+        None => Palette::default(),
+    };
+    let file_path = args.get(1).map(String::as_str).unwrap_or("7_Graphics.nes");
+    let file_bytes = match fs::read(file_path) {
         Ok(bytes) => bytes,
         Err(e) => {
             eprintln!("Could not read file: {}", e);
             return;
         }
     };
+    let raw_bytes = match extract_rom(file_bytes, entry_name.as_deref()) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Could not load ROM: {}", e);
+            return;
+        }
+    };
 
-    let rom = Rom::new(&raw_bytes);
+    // Without patches on the command line, pick up game.ips, game.ups or game.bps next to game.nes
+    let patch_paths: Vec<String> = if args.len() > 2 {
+        args[2..].to_vec()
+    } else {
+        ["ips", "ups", "bps"]
+            .iter()
+            .map(|extension| Path::new(file_path).with_extension(extension))
+            .filter(|path| path.is_file())
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect()
+    };
 
-    let mut bus = Bus::new(rom);
-    let mut cpu = Cpu::new();
-    let frame = bus.ppu.debug_draw_pattern_tables();
-    image::save_buffer("pattern_tables.png", &frame, 256, 128, Rgb8).expect("Failed to save image");
+    let mut patches = Vec::new();
+    for path in &patch_paths {
+        match fs::read(path) {
+            Ok(patch) => {
+                eprintln!("Applying patch {}", path);
+                patches.push(patch);
+            }
+            Err(e) => {
+                eprintln!("Could not read patch {}: {}", path, e);
+                return;
+            }
+        }
+    }
+    let patches: Vec<&[u8]> = patches.iter().map(Vec::as_slice).collect();
 
-    cpu.reset(&mut bus);
+    let mut rom = match Rom::with_patches(&raw_bytes, &patches) {
+        Ok(rom) => rom,
+        Err(e) => {
+            eprintln!("Could not load ROM: {}", e);
+            return;
+        }
+    };
+
+    // Fix up known bad headers when there is a game database in the working directory
+    if let Ok(csv) = fs::read_to_string("gamedb.csv") {
+        match GameDatabase::from_csv(&csv) {
+            Ok(database) => {
+                for correction in rom.apply_database(&database) {
+                    eprintln!("Corrected header: {}", correction);
+                }
+            }
+            Err(e) => eprintln!("Could not load game database: {}", e),
+        }
+    }
+
+    eprintln!("{}", rom.info());
+    for warning in &rom.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let region = rom.region();
+    let mut nes = match Nes::new(rom, region) {
+        Ok(nes) => nes,
+        Err(e) => {
+            eprintln!("Could not start emulation: {}", e);
+            return;
+        }
+    };
+
+    nes.bus.ppu.palette = palette;
+
+    // Battery-backed PRG RAM lives in game.sav next to game.nes
+    let save_path = Path::new(file_path).with_extension("sav");
+    let mut saved_ram = Vec::new();
+    if nes.has_battery_backed_ram() {
+        if let Ok(data) = fs::read(&save_path) {
+            nes.import_save_ram(&data);
+        }
+        saved_ram = nes.export_save_ram();
+    }
+
+    let frame = nes.bus.ppu.debug_draw_pattern_tables();
+    image::save_buffer("pattern_tables.png", &frame, 256, 128, Rgb8).expect("Failed to save image");
 
     // for _ in 0..1000000 {
-    //     println!("{}", cpu.trace(&bus));
-    //     cpu.emulate_cpu(&mut bus);
+    //     println!("{}", nes.trace());
+    //     nes.step_instruction().unwrap();
     // }
 
-    while !cpu.halted {
-        println!("{}", cpu.trace(&bus));
-        cpu.emulate_cpu(&mut bus);
+    let mut last_save_frame = nes.bus.ppu.frame();
+    while !nes.cpu.halted {
+        println!("{}", nes.trace());
+        if let Err(e) = nes.step_instruction() {
+            eprintln!("Emulation stopped: {}", e);
+            break;
+        }
+
+        // Flush the save about once a second so a crash loses little progress
+        if nes.has_battery_backed_ram() && nes.bus.ppu.frame() >= last_save_frame + 60 {
+            last_save_frame = nes.bus.ppu.frame();
+            write_save(&nes, &save_path, &mut saved_ram);
+        }
+    }
+
+    if nes.has_battery_backed_ram() {
+        write_save(&nes, &save_path, &mut saved_ram);
     }
 
-    let output_frame = bus.ppu.debug_draw_nametable();
-    image::save_buffer("nametable.png", &output_frame, 512, 240, Rgb8).expect("Failed to save image");
+    let picture = nes.bus.ppu.frame_rgb();
+    image::save_buffer("frame.png", &picture, 256, 240, Rgb8).expect("Failed to save image");
+
+    let nametables = nes.bus.ppu.debug_draw_nametables(&nametable_view);
+    save_debug_image("nametables.png", &nametables);
+    save_debug_image("sprites.png", &nes.bus.ppu.debug_draw_sprites());
+    save_debug_image("palettes.png", &nes.bus.ppu.debug_draw_palettes());
+    if let Err(e) = fs::write("sprites.txt", nes.bus.ppu.debug_sprite_table()) {
+        eprintln!("Could not write sprites.txt: {}", e);
+    }
+}
+
+// Removes an option and its value from the arguments, failing when the value is missing
+fn take_option(args: &mut Vec<String>, option: &str) -> Result<Option<String>, ()> {
+    match args.iter().position(|arg| arg == option) {
+        Some(index) if index + 1 < args.len() => {
+            let value = args.remove(index + 1);
+            args.remove(index);
+            Ok(Some(value))
+        }
+        Some(_) => Err(()),
+        None => Ok(None),
+    }
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|arg| arg == flag) {
+        Some(index) => {
+            args.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+fn save_debug_image(path: &str, image: &DebugImage) {
+    image::save_buffer(
+        path,
+        &image.pixels,
+        image.width as u32,
+        image.height as u32,
+        Rgb8,
+    )
+    .expect("Failed to save image");
+}
+
+// Writes the save RAM when it has changed since the last write
+fn write_save(nes: &Nes, save_path: &Path, saved_ram: &mut Vec<u8>) {
+    let ram = nes.export_save_ram();
+    if ram == *saved_ram {
+        return;
+    }
+    match fs::write(save_path, &ram) {
+        Ok(()) => *saved_ram = ram,
+        Err(e) => eprintln!("Could not write {}: {}", save_path.display(), e),
+    }
 }
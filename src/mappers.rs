@@ -0,0 +1,97 @@
+use crate::cartridge::Mirroring;
+use crate::error::NesError;
+use crate::rom::Rom;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod axrom;
+mod cnrom;
+mod gxrom;
+mod mmc1;
+mod mmc3;
+mod nrom;
+mod uxrom;
+
+pub use axrom::Axrom;
+pub use cnrom::Cnrom;
+pub use gxrom::Gxrom;
+pub use mmc1::Mmc1;
+pub use mmc3::Mmc3;
+pub use nrom::Nrom;
+pub use uxrom::Uxrom;
+
+// The cartridge side of the console. A mapper owns the PRG and CHR memory and decides how the
+// CPU and PPU address spaces map onto it
+pub trait Mapper {
+    // CPU reads in $4020-$FFFF, None when nothing on the cartridge responds
+    fn cpu_read(&self, address: u16) -> Option<u8>;
+
+    // CPU writes in $4020-$FFFF, false when nothing on the cartridge responds
+    fn cpu_write(&mut self, address: u16, value: u8) -> bool;
+
+    // Called once per CPU cycle, for mappers that count cycles
+    fn cpu_tick(&mut self) {}
+
+    // Called with each address the PPU puts on its bus, for mappers that watch the PPU fetches
+    fn ppu_address(&mut self, _address: u16) {}
+
+    // PPU pattern table accesses in $0000-$1FFF
+    fn ppu_read(&self, address: u16) -> u8;
+
+    fn ppu_write(&mut self, address: u16, value: u8);
+
+    fn mirroring(&self) -> Mirroring;
+
+    fn irq(&self) -> bool {
+        false
+    }
+
+    // Work RAM at $6000-$7FFF, which holds the save data on battery-backed boards
+    fn prg_ram(&self) -> &[u8] {
+        &[]
+    }
+
+    fn prg_ram_mut(&mut self) -> &mut [u8] {
+        &mut []
+    }
+}
+
+// The CPU bus and the PPU both talk to the cartridge
+pub type SharedMapper = Rc<RefCell<dyn Mapper>>;
+
+// The CHR ROM, or CHR RAM when the ROM has none, and whether it is RAM. Boards with CHR RAM
+// have at least a whole 8 KB pattern table, whatever the header or game database says
+fn chr_memory(chr_rom: Vec<u8>, chr_ram_size: usize) -> (Vec<u8>, bool) {
+    if chr_rom.is_empty() {
+        (vec![0; chr_ram_size.max(8192)], true)
+    } else {
+        (chr_rom, false)
+    }
+}
+
+pub fn create_mapper(rom: Rom) -> Result<SharedMapper, NesError> {
+    let trainer = rom.trainer.clone();
+    let mapper: SharedMapper = match rom.mapper {
+        0 => Rc::new(RefCell::new(Nrom::new(rom))),
+        1 => Rc::new(RefCell::new(Mmc1::new(rom))),
+        2 => Rc::new(RefCell::new(Uxrom::new(rom))),
+        3 => Rc::new(RefCell::new(Cnrom::new(rom))),
+        4 => Rc::new(RefCell::new(Mmc3::new(rom))),
+        7 => Rc::new(RefCell::new(Axrom::new(rom))),
+        66 => Rc::new(RefCell::new(Gxrom::new(rom))),
+        mapper => return Err(NesError::UnsupportedMapper(mapper)),
+    };
+
+    // The trainer is copied into PRG RAM at $7000-$71FF before the game starts, which fails on
+    // boards without PRG RAM there
+    if let Some(trainer) = trainer {
+        let mut cartridge = mapper.borrow_mut();
+        for (offset, &value) in trainer.iter().enumerate() {
+            if !cartridge.cpu_write(0x7000 + offset as u16, value) {
+                return Err(NesError::TrainerWithoutPrgRam);
+            }
+        }
+    }
+
+    Ok(mapper)
+}
@@ -0,0 +1,63 @@
+use crate::cartridge::Mirroring;
+use crate::mappers::{Mapper, chr_memory};
+use crate::rom::Rom;
+
+// Mapper 7, the AxROM boards. A 32 KB PRG bank is switched in at $8000, and the same register
+// picks which of the two nametables fills the whole screen
+pub struct Axrom {
+    prg_rom: Vec<u8>,
+    chr_memory: Vec<u8>,
+    chr_is_ram: bool,
+    bank_select: u8,
+}
+
+impl Axrom {
+    pub fn new(rom: Rom) -> Self {
+        let (chr_memory, chr_is_ram) = chr_memory(rom.chr_rom, rom.cartridge_info.chr_ram_size);
+
+        Axrom {
+            prg_rom: rom.prg_rom,
+            chr_memory,
+            chr_is_ram,
+            bank_select: 0,
+        }
+    }
+}
+
+impl Mapper for Axrom {
+    fn cpu_read(&self, address: u16) -> Option<u8> {
+        if address < 0x8000 {
+            return None;
+        }
+        let bank = (self.bank_select & 0x07) as usize;
+        let index = bank * 32768 + (address & 0x7FFF) as usize;
+        Some(self.prg_rom[index % self.prg_rom.len()])
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) -> bool {
+        if address < 0x8000 {
+            return false;
+        }
+        self.bank_select = value;
+        true
+    }
+
+    fn ppu_read(&self, address: u16) -> u8 {
+        self.chr_memory[address as usize % self.chr_memory.len()]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let index = address as usize % self.chr_memory.len();
+            self.chr_memory[index] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        if self.bank_select & 0x10 == 0 {
+            Mirroring::SingleScreenA
+        } else {
+            Mirroring::SingleScreenB
+        }
+    }
+}
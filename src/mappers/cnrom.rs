@@ -0,0 +1,63 @@
+use crate::cartridge::Mirroring;
+use crate::mappers::{Mapper, chr_memory};
+use crate::rom::Rom;
+
+// Mapper 3, the CNROM boards. Fixed PRG ROM with a switchable 8 KB CHR bank
+pub struct Cnrom {
+    prg_rom: Vec<u8>,
+    chr_memory: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    chr_bank: u8,
+}
+
+impl Cnrom {
+    pub fn new(rom: Rom) -> Self {
+        let (chr_memory, chr_is_ram) = chr_memory(rom.chr_rom, rom.cartridge_info.chr_ram_size);
+
+        Cnrom {
+            prg_rom: rom.prg_rom,
+            chr_memory,
+            chr_is_ram,
+            mirroring: rom.cartridge_info.mirroring,
+            chr_bank: 0,
+        }
+    }
+
+    fn chr_index(&self, address: u16) -> usize {
+        (self.chr_bank as usize * 8192 + address as usize) % self.chr_memory.len()
+    }
+}
+
+impl Mapper for Cnrom {
+    fn cpu_read(&self, address: u16) -> Option<u8> {
+        match address {
+            // 16 KB boards mirror their PRG ROM into $C000
+            0x8000.. => Some(self.prg_rom[(address - 0x8000) as usize % self.prg_rom.len()]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) -> bool {
+        if address < 0x8000 {
+            return false;
+        }
+        self.chr_bank = value;
+        true
+    }
+
+    fn ppu_read(&self, address: u16) -> u8 {
+        self.chr_memory[self.chr_index(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let index = self.chr_index(address);
+            self.chr_memory[index] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
@@ -0,0 +1,66 @@
+use crate::cartridge::Mirroring;
+use crate::mappers::{Mapper, chr_memory};
+use crate::rom::Rom;
+
+// Mapper 66, the GxROM boards. One register selects a 32 KB PRG bank in bits 4-5 and an 8 KB
+// CHR bank in bits 0-1
+pub struct Gxrom {
+    prg_rom: Vec<u8>,
+    chr_memory: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    bank_select: u8,
+}
+
+impl Gxrom {
+    pub fn new(rom: Rom) -> Self {
+        let (chr_memory, chr_is_ram) = chr_memory(rom.chr_rom, rom.cartridge_info.chr_ram_size);
+
+        Gxrom {
+            prg_rom: rom.prg_rom,
+            chr_memory,
+            chr_is_ram,
+            mirroring: rom.cartridge_info.mirroring,
+            bank_select: 0,
+        }
+    }
+
+    fn chr_index(&self, address: u16) -> usize {
+        let bank = (self.bank_select & 0x03) as usize;
+        (bank * 8192 + address as usize) % self.chr_memory.len()
+    }
+}
+
+impl Mapper for Gxrom {
+    fn cpu_read(&self, address: u16) -> Option<u8> {
+        if address < 0x8000 {
+            return None;
+        }
+        let bank = ((self.bank_select >> 4) & 0x03) as usize;
+        let index = bank * 32768 + (address & 0x7FFF) as usize;
+        Some(self.prg_rom[index % self.prg_rom.len()])
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) -> bool {
+        if address < 0x8000 {
+            return false;
+        }
+        self.bank_select = value;
+        true
+    }
+
+    fn ppu_read(&self, address: u16) -> u8 {
+        self.chr_memory[self.chr_index(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let index = self.chr_index(address);
+            self.chr_memory[index] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
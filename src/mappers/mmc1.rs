@@ -0,0 +1,176 @@
+use crate::cartridge::Mirroring;
+use crate::mappers::{Mapper, chr_memory};
+use crate::rom::Rom;
+
+// Mapper 1, the SxROM boards. Registers are loaded one bit at a time through a serial shift
+// register, with the fifth write selecting the register by address
+pub struct Mmc1 {
+    prg_rom: Vec<u8>,
+    chr_memory: Vec<u8>,
+    chr_is_ram: bool,
+    prg_ram: Vec<u8>,
+    shift_register: u8,
+    shift_count: u8,
+    control: u8,
+    chr_bank_0: u8,
+    chr_bank_1: u8,
+    prg_bank: u8,
+    cycles_since_write: u8,
+}
+
+impl Mmc1 {
+    pub fn new(rom: Rom) -> Self {
+        let (chr_memory, chr_is_ram) = chr_memory(rom.chr_rom, rom.cartridge_info.chr_ram_size);
+
+        Mmc1 {
+            prg_rom: rom.prg_rom,
+            chr_memory,
+            chr_is_ram,
+            prg_ram: vec![0; rom.cartridge_info.prg_ram_size + rom.cartridge_info.prg_nvram_size],
+            shift_register: 0,
+            shift_count: 0,
+            // Power on with the last PRG bank fixed at $C000
+            control: 0x0C,
+            chr_bank_0: 0,
+            chr_bank_1: 0,
+            prg_bank: 0,
+            cycles_since_write: u8::MAX,
+        }
+    }
+
+    // NES 2.0 headers can say the board has no PRG RAM at all
+    fn prg_ram_enabled(&self) -> bool {
+        self.prg_bank & 0x10 == 0 && !self.prg_ram.is_empty()
+    }
+
+    fn prg_ram_index(&self, address: u16) -> usize {
+        (address & 0x1FFF) as usize % self.prg_ram.len()
+    }
+
+    fn prg_rom_index(&self, address: u16) -> usize {
+        let bank_count = (self.prg_rom.len() / 16384).max(1);
+        // SUROM uses bit 4 of the CHR bank to select a 256 KB half of the PRG ROM
+        let outer_bank = if self.prg_rom.len() > 262144 {
+            (self.chr_bank_0 & 0x10) as usize
+        } else {
+            0
+        };
+        let inner_bank = (self.prg_bank & 0x0F) as usize;
+        let last_bank = (bank_count - 1).min(outer_bank | 0x0F);
+
+        let bank = match ((self.control >> 2) & 0x03, address) {
+            // 32 KB mode ignores the low bit of the bank number
+            (0 | 1, 0x8000..=0xBFFF) => outer_bank | (inner_bank & 0x0E),
+            (0 | 1, _) => outer_bank | inner_bank | 0x01,
+            // Fix the first bank at $8000 and switch $C000
+            (2, 0x8000..=0xBFFF) => outer_bank,
+            (2, _) => outer_bank | inner_bank,
+            // Fix the last bank at $C000 and switch $8000
+            (_, 0x8000..=0xBFFF) => outer_bank | inner_bank,
+            (_, _) => last_bank,
+        };
+        // Boards with less than one bank of PRG ROM mirror it
+        ((bank % bank_count) * 16384 + (address & 0x3FFF) as usize) % self.prg_rom.len()
+    }
+
+    fn chr_index(&self, address: u16) -> usize {
+        let index = if self.control & 0x10 == 0 {
+            // 8 KB mode ignores the low bit of the bank number
+            (self.chr_bank_0 & 0x1E) as usize * 4096 + (address & 0x1FFF) as usize
+        } else if address < 0x1000 {
+            self.chr_bank_0 as usize * 4096 + (address & 0x0FFF) as usize
+        } else {
+            self.chr_bank_1 as usize * 4096 + (address & 0x0FFF) as usize
+        };
+        index % self.chr_memory.len()
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x8000..=0x9FFF => self.control = value,
+            0xA000..=0xBFFF => self.chr_bank_0 = value,
+            0xC000..=0xDFFF => self.chr_bank_1 = value,
+            _ => self.prg_bank = value,
+        }
+    }
+}
+
+impl Mapper for Mmc1 {
+    fn cpu_read(&self, address: u16) -> Option<u8> {
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                Some(self.prg_ram[self.prg_ram_index(address)])
+            }
+            0x8000.. => Some(self.prg_rom[self.prg_rom_index(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) -> bool {
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                let index = self.prg_ram_index(address);
+                self.prg_ram[index] = value;
+                true
+            }
+            0x8000.. => {
+                // Writes on consecutive cycles, like the double write of a read-modify-write
+                // instruction, only the first one reaches the shift register
+                let consecutive = self.cycles_since_write <= 1;
+                self.cycles_since_write = 0;
+                if consecutive {
+                    return true;
+                }
+
+                if value & 0x80 != 0 {
+                    self.shift_register = 0;
+                    self.shift_count = 0;
+                    self.control |= 0x0C;
+                    return true;
+                }
+
+                self.shift_register |= (value & 0x01) << self.shift_count;
+                self.shift_count += 1;
+                if self.shift_count == 5 {
+                    self.write_register(address, self.shift_register);
+                    self.shift_register = 0;
+                    self.shift_count = 0;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn cpu_tick(&mut self) {
+        self.cycles_since_write = self.cycles_since_write.saturating_add(1);
+    }
+
+    fn ppu_read(&self, address: u16) -> u8 {
+        self.chr_memory[self.chr_index(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let index = self.chr_index(address);
+            self.chr_memory[index] = value;
+        }
+    }
+
+    fn prg_ram(&self) -> &[u8] {
+        &self.prg_ram
+    }
+
+    fn prg_ram_mut(&mut self) -> &mut [u8] {
+        &mut self.prg_ram
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.control & 0x03 {
+            0 => Mirroring::SingleScreenA,
+            1 => Mirroring::SingleScreenB,
+            2 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal,
+        }
+    }
+}
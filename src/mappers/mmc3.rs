@@ -0,0 +1,198 @@
+use crate::cartridge::Mirroring;
+use crate::mappers::{Mapper, chr_memory};
+use crate::rom::Rom;
+
+// Mapper 4, the TxROM boards. Eight bank registers switch 8 KB of PRG and 1 KB of CHR at a
+// time, and a scanline counter clocked by rising edges on PPU address line A12 raises IRQs
+pub struct Mmc3 {
+    prg_rom: Vec<u8>,
+    chr_memory: Vec<u8>,
+    chr_is_ram: bool,
+    prg_ram: Vec<u8>,
+    bank_select: u8,
+    bank_registers: [u8; 8],
+    mirroring: Mirroring,
+    prg_ram_enabled: bool,
+    prg_ram_write_protected: bool,
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_reload: bool,
+    irq_enabled: bool,
+    irq_line: bool,
+    a12: bool,
+    cycles_a12_low: u8,
+}
+
+impl Mmc3 {
+    pub fn new(rom: Rom) -> Self {
+        let (chr_memory, chr_is_ram) = chr_memory(rom.chr_rom, rom.cartridge_info.chr_ram_size);
+
+        Mmc3 {
+            prg_rom: rom.prg_rom,
+            chr_memory,
+            chr_is_ram,
+            prg_ram: vec![0; rom.cartridge_info.prg_ram_size + rom.cartridge_info.prg_nvram_size],
+            bank_select: 0,
+            bank_registers: [0, 2, 4, 5, 6, 7, 0, 1],
+            mirroring: rom.cartridge_info.mirroring,
+            prg_ram_enabled: true,
+            prg_ram_write_protected: false,
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_reload: false,
+            irq_enabled: false,
+            irq_line: false,
+            a12: false,
+            cycles_a12_low: 0,
+        }
+    }
+
+    fn prg_rom_index(&self, address: u16) -> usize {
+        let bank_count = (self.prg_rom.len() / 8192).max(1);
+        let second_last_bank = bank_count.saturating_sub(2);
+        let swapped = self.bank_select & 0x40 != 0;
+
+        let bank = match (address, swapped) {
+            (0x8000..=0x9FFF, false) | (0xC000..=0xDFFF, true) => {
+                (self.bank_registers[6] & 0x3F) as usize
+            }
+            (0x8000..=0x9FFF, true) | (0xC000..=0xDFFF, false) => second_last_bank,
+            (0xA000..=0xBFFF, _) => (self.bank_registers[7] & 0x3F) as usize,
+            _ => bank_count - 1,
+        };
+        // Boards with less than one bank of PRG ROM mirror it
+        ((bank % bank_count) * 8192 + (address & 0x1FFF) as usize) % self.prg_rom.len()
+    }
+
+    fn chr_index(&self, address: u16) -> usize {
+        // CHR A12 inversion swaps the 2 KB banks and 1 KB banks between pattern tables
+        let address = if self.bank_select & 0x80 != 0 {
+            address ^ 0x1000
+        } else {
+            address
+        };
+
+        let bank = match address {
+            0x0000..=0x07FF => (self.bank_registers[0] & 0xFE) as usize + (address >> 10) as usize,
+            0x0800..=0x0FFF => {
+                (self.bank_registers[1] & 0xFE) as usize + ((address >> 10) & 0x01) as usize
+            }
+            _ => self.bank_registers[2 + ((address >> 10) & 0x03) as usize] as usize,
+        };
+        (bank * 1024 + (address & 0x03FF) as usize) % self.chr_memory.len()
+    }
+
+    fn clock_irq_counter(&mut self) {
+        if self.irq_counter == 0 || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        if self.irq_counter == 0 && self.irq_enabled {
+            self.irq_line = true;
+        }
+    }
+}
+
+impl Mapper for Mmc3 {
+    fn cpu_read(&self, address: u16) -> Option<u8> {
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled && !self.prg_ram.is_empty() => {
+                Some(self.prg_ram[(address & 0x1FFF) as usize % self.prg_ram.len()])
+            }
+            0x8000.. => Some(self.prg_rom[self.prg_rom_index(address)]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) -> bool {
+        let even = address & 0x01 == 0;
+        match address {
+            0x6000..=0x7FFF if self.prg_ram_enabled && !self.prg_ram.is_empty() => {
+                if !self.prg_ram_write_protected {
+                    let index = (address & 0x1FFF) as usize % self.prg_ram.len();
+                    self.prg_ram[index] = value;
+                }
+            }
+            0x8000..=0x9FFF if even => self.bank_select = value,
+            0x8000..=0x9FFF => {
+                self.bank_registers[(self.bank_select & 0x07) as usize] = value;
+            }
+            0xA000..=0xBFFF if even => {
+                // Boards wired for four-screen VRAM ignore the mirroring register
+                if self.mirroring != Mirroring::FourScreen {
+                    self.mirroring = if value & 0x01 == 0 {
+                        Mirroring::Vertical
+                    } else {
+                        Mirroring::Horizontal
+                    };
+                }
+            }
+            0xA000..=0xBFFF => {
+                self.prg_ram_enabled = value & 0x80 != 0;
+                self.prg_ram_write_protected = value & 0x40 != 0;
+            }
+            0xC000..=0xDFFF if even => self.irq_latch = value,
+            0xC000..=0xDFFF => {
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            }
+            0xE000.. if even => {
+                self.irq_enabled = false;
+                self.irq_line = false;
+            }
+            0xE000.. => self.irq_enabled = true,
+            _ => return false,
+        }
+        true
+    }
+
+    fn cpu_tick(&mut self) {
+        if !self.a12 {
+            self.cycles_a12_low = self.cycles_a12_low.saturating_add(1);
+        }
+    }
+
+    // The counter is clocked when A12 rises after staying low for a few CPU cycles. The filter
+    // ignores the short low periods between the sprite pattern fetches within a scanline
+    fn ppu_address(&mut self, address: u16) {
+        let a12 = address & 0x1000 != 0;
+        if a12 && !self.a12 {
+            if self.cycles_a12_low >= 3 {
+                self.clock_irq_counter();
+            }
+        } else if !a12 && self.a12 {
+            self.cycles_a12_low = 0;
+        }
+        self.a12 = a12;
+    }
+
+    fn ppu_read(&self, address: u16) -> u8 {
+        self.chr_memory[self.chr_index(address)]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let index = self.chr_index(address);
+            self.chr_memory[index] = value;
+        }
+    }
+
+    fn prg_ram(&self) -> &[u8] {
+        &self.prg_ram
+    }
+
+    fn prg_ram_mut(&mut self) -> &mut [u8] {
+        &mut self.prg_ram
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn irq(&self) -> bool {
+        self.irq_line
+    }
+}
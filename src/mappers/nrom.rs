@@ -0,0 +1,75 @@
+use crate::cartridge::Mirroring;
+use crate::mappers::{Mapper, chr_memory};
+use crate::rom::Rom;
+
+// Mapper 0, no bank switching. NROM-128 boards have 16 KB of PRG ROM mirrored into $C000
+pub struct Nrom {
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr_memory: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+}
+
+impl Nrom {
+    pub fn new(rom: Rom) -> Self {
+        let (chr_memory, chr_is_ram) = chr_memory(rom.chr_rom, rom.cartridge_info.chr_ram_size);
+
+        Nrom {
+            prg_rom: rom.prg_rom,
+            prg_ram: vec![0; rom.cartridge_info.prg_ram_size + rom.cartridge_info.prg_nvram_size],
+            chr_memory,
+            chr_is_ram,
+            mirroring: rom.cartridge_info.mirroring,
+        }
+    }
+}
+
+impl Mapper for Nrom {
+    fn cpu_read(&self, address: u16) -> Option<u8> {
+        match address {
+            // Family Basic style work RAM, which the blargg test ROMs also write their results to
+            0x6000..=0x7FFF if !self.prg_ram.is_empty() => {
+                Some(self.prg_ram[(address - 0x6000) as usize % self.prg_ram.len()])
+            }
+            0x8000.. => Some(self.prg_rom[(address - 0x8000) as usize % self.prg_rom.len()]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) -> bool {
+        match address {
+            0x6000..=0x7FFF if !self.prg_ram.is_empty() => {
+                let index = (address - 0x6000) as usize % self.prg_ram.len();
+                self.prg_ram[index] = value;
+                true
+            }
+            // Writes to ROM are ignored
+            0x8000.. => true,
+            _ => false,
+        }
+    }
+
+    fn ppu_read(&self, address: u16) -> u8 {
+        self.chr_memory[address as usize % self.chr_memory.len()]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let index = address as usize % self.chr_memory.len();
+            self.chr_memory[index] = value;
+        }
+    }
+
+    fn prg_ram(&self) -> &[u8] {
+        &self.prg_ram
+    }
+
+    fn prg_ram_mut(&mut self) -> &mut [u8] {
+        &mut self.prg_ram
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
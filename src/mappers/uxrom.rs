@@ -0,0 +1,64 @@
+use crate::cartridge::Mirroring;
+use crate::mappers::{Mapper, chr_memory};
+use crate::rom::Rom;
+
+// Mapper 2, the UxROM boards. A 16 KB PRG bank is switched in at $8000 and the last bank is
+// fixed at $C000
+pub struct Uxrom {
+    prg_rom: Vec<u8>,
+    chr_memory: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    prg_bank: u8,
+}
+
+impl Uxrom {
+    pub fn new(rom: Rom) -> Self {
+        let (chr_memory, chr_is_ram) = chr_memory(rom.chr_rom, rom.cartridge_info.chr_ram_size);
+
+        Uxrom {
+            prg_rom: rom.prg_rom,
+            chr_memory,
+            chr_is_ram,
+            mirroring: rom.cartridge_info.mirroring,
+            prg_bank: 0,
+        }
+    }
+}
+
+impl Mapper for Uxrom {
+    fn cpu_read(&self, address: u16) -> Option<u8> {
+        let bank_count = (self.prg_rom.len() / 16384).max(1);
+        let bank = match address {
+            0x8000..=0xBFFF => self.prg_bank as usize % bank_count,
+            0xC000.. => bank_count - 1,
+            _ => return None,
+        };
+        self.prg_rom
+            .get(bank * 16384 + (address & 0x3FFF) as usize)
+            .copied()
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) -> bool {
+        if address < 0x8000 {
+            return false;
+        }
+        self.prg_bank = value;
+        true
+    }
+
+    fn ppu_read(&self, address: u16) -> u8 {
+        self.chr_memory[address as usize % self.chr_memory.len()]
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let index = address as usize % self.chr_memory.len();
+            self.chr_memory[index] = value;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
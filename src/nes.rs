@@ -0,0 +1,200 @@
+use crate::bus::Bus;
+use crate::cpu::Cpu;
+use crate::error::NesError;
+use crate::rom::Rom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Ntsc,
+    Pal,
+    // The Famiclone timing, PAL's frame with NTSC's CPU to PPU ratio
+    Dendy,
+}
+
+impl Region {
+    // The PPU runs 3 dots per CPU cycle on NTSC and 3.2 on PAL, so count in fifths of a dot
+    pub fn ppu_dots_per_cycle_fifths(self) -> u32 {
+        match self {
+            Region::Ntsc | Region::Dendy => 15,
+            Region::Pal => 16,
+        }
+    }
+
+    pub fn scanlines_per_frame(self) -> u16 {
+        match self {
+            Region::Ntsc => 262,
+            Region::Pal | Region::Dendy => 312,
+        }
+    }
+
+    // Dendy holds off vblank until 50 scanlines after the picture ends
+    pub fn vblank_scanline(self) -> u16 {
+        match self {
+            Region::Ntsc | Region::Pal => 241,
+            Region::Dendy => 291,
+        }
+    }
+}
+
+pub struct Nes {
+    pub cpu: Cpu,
+    pub bus: Bus,
+    cycle_target: u64,
+    has_battery_backed_ram: bool,
+}
+
+impl Nes {
+    pub fn new(rom: Rom, region: Region) -> Result<Self, NesError> {
+        let has_battery_backed_ram = rom.cartridge_info.has_battery_backed_ram;
+        let mut bus = Bus::new(rom, region)?;
+        let mut cpu = Cpu::new();
+
+        // The CPU/PPU alignment at power on varies between consoles, start the PPU 7 dots
+        // ahead to line up with the reference trace logs
+        for _ in 0..7 {
+            bus.ppu.tick();
+        }
+        cpu.reset(&mut bus);
+
+        let cycle_target = bus.cycles;
+        Ok(Nes {
+            cpu,
+            bus,
+            cycle_target,
+            has_battery_backed_ram,
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.cpu.halted = false;
+        self.cpu.reset(&mut self.bus);
+        self.cycle_target = self.bus.cycles;
+    }
+
+    pub fn has_battery_backed_ram(&self) -> bool {
+        self.has_battery_backed_ram
+    }
+
+    // A copy of the cartridge's PRG RAM, the contents of a .sav file on battery-backed boards
+    pub fn export_save_ram(&self) -> Vec<u8> {
+        self.bus.mapper.borrow().prg_ram().to_vec()
+    }
+
+    // Loads saved PRG RAM. Data longer than the cartridge's RAM is cut off, and shorter data
+    // leaves the rest of the RAM untouched
+    pub fn import_save_ram(&mut self, data: &[u8]) {
+        let mut mapper = self.bus.mapper.borrow_mut();
+        let prg_ram = mapper.prg_ram_mut();
+        let length = data.len().min(prg_ram.len());
+        prg_ram[..length].copy_from_slice(&data[..length]);
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.bus.cycles
+    }
+
+    // Runs one CPU instruction and returns the number of cycles it took, including any OAM DMA
+    // it started
+    pub fn step_instruction(&mut self) -> Result<u16, NesError> {
+        let cycles = self.execute_instruction();
+        self.cycle_target = self.bus.cycles;
+        cycles
+    }
+
+    // Advances the master clock by one CPU cycle. Instructions are executed whole, so the next
+    // one only starts once all of the previous instruction's cycles have elapsed
+    pub fn step_cycle(&mut self) -> Result<(), NesError> {
+        self.cycle_target += 1;
+        while self.bus.cycles < self.cycle_target {
+            self.execute_instruction()?;
+        }
+        Ok(())
+    }
+
+    pub fn run_frame(&mut self) -> Result<(), NesError> {
+        let frame = self.bus.ppu.frame();
+        while self.bus.ppu.frame() == frame {
+            self.step_instruction()?;
+        }
+        Ok(())
+    }
+
+    // A jammed CPU never finishes another instruction, but the rest of the console keeps running
+    fn execute_instruction(&mut self) -> Result<u16, NesError> {
+        if self.cpu.halted {
+            self.bus.tick();
+            Ok(1)
+        } else {
+            self.cpu.emulate_cpu(&mut self.bus)
+        }
+    }
+
+    pub fn trace(&self) -> String {
+        format!(
+            "{} Cycle: {} PPU_cycle: {} ({}, {})",
+            self.cpu.trace(&self.bus),
+            self.bus.cycles,
+            self.bus.ppu.cycles(),
+            self.bus.ppu.scanline(),
+            self.bus.ppu.dot(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An NROM image running the given code from $C000
+    fn test_nes(code: &[u8]) -> Nes {
+        let mut image = vec![0x4E, 0x45, 0x53, 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let mut prg_rom = vec![0xEA; 0x4000];
+        prg_rom[..code.len()].copy_from_slice(code);
+        prg_rom[0x3FFC..].copy_from_slice(&[0x00, 0xC0, 0x00, 0xC0]);
+        image.extend(prg_rom);
+        image.extend(vec![0; 0x2000]);
+        Nes::new(Rom::new(&image).unwrap(), Region::Ntsc).unwrap()
+    }
+
+    #[test]
+    fn oam_dma_stalls_the_instruction_that_starts_it() {
+        // LDA #$02, STA $4014, NOP
+        let mut nes = test_nes(&[0xA9, 0x02, 0x8D, 0x14, 0x40, 0xEA]);
+        nes.bus.ram[0x200] = 0x5A;
+        assert_eq!(nes.step_instruction(), Ok(2));
+
+        // The write ends on an odd cycle, so the DMA takes the halt cycle, an alignment cycle
+        // and 256 reads and writes on top of the store's 4 cycles
+        let start = nes.cycles();
+        assert_eq!(nes.step_instruction(), Ok(4 + 514));
+        assert_eq!(nes.cycles() - start, 4 + 514);
+        assert_eq!(nes.bus.ppu.peek_register(0x2004), 0x5A);
+
+        assert_eq!(nes.step_instruction(), Ok(2));
+    }
+
+    #[test]
+    fn nmi_raised_during_oam_dma_is_taken_next() {
+        // LDA #$80, STA $2000, LDA #$02, then STA $4014 and JMP back to it forever. The NMI
+        // vector points at $EAEA
+        let mut nes = test_nes(&[
+            0xA9, 0x80, 0x8D, 0x00, 0x20, 0xA9, 0x02, 0x8D, 0x14, 0x40, 0x4C, 0x07, 0xC0,
+        ]);
+        for _ in 0..3 {
+            nes.step_instruction().unwrap();
+        }
+
+        // Run until vblank starts during one of the DMAs
+        loop {
+            let in_vblank = nes.bus.ppu.peek_register(0x2002) & 0x80 != 0;
+            let cycles = nes.step_instruction().unwrap();
+            let entered_vblank = !in_vblank && nes.bus.ppu.peek_register(0x2002) & 0x80 != 0;
+            if cycles > 500 && entered_vblank {
+                break;
+            }
+        }
+
+        nes.step_instruction().unwrap();
+        assert!(nes.trace().starts_with("EAEA"));
+    }
+}
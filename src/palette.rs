@@ -0,0 +1,129 @@
+use crate::error::NesError;
+
+// The 64 colours of the NTSC 2C02, without emphasis
+const NTSC_2C02: [[u8; 3]; 64] = [
+    [84, 84, 84],
+    [0, 30, 116],
+    [8, 16, 144],
+    [48, 0, 136],
+    [68, 0, 100],
+    [92, 0, 48],
+    [84, 4, 0],
+    [60, 24, 0],
+    [32, 42, 0],
+    [8, 58, 0],
+    [0, 64, 0],
+    [0, 60, 0],
+    [0, 50, 60],
+    [0, 0, 0],
+    [0, 0, 0],
+    [0, 0, 0],
+    [152, 150, 152],
+    [8, 76, 196],
+    [48, 50, 236],
+    [92, 30, 228],
+    [136, 20, 176],
+    [160, 20, 100],
+    [152, 34, 32],
+    [120, 60, 0],
+    [84, 90, 0],
+    [40, 114, 0],
+    [8, 124, 0],
+    [0, 118, 40],
+    [0, 102, 120],
+    [0, 0, 0],
+    [0, 0, 0],
+    [0, 0, 0],
+    [236, 238, 236],
+    [76, 154, 236],
+    [120, 124, 236],
+    [176, 98, 236],
+    [228, 84, 236],
+    [236, 88, 180],
+    [236, 106, 100],
+    [212, 136, 32],
+    [160, 170, 0],
+    [116, 196, 0],
+    [76, 208, 32],
+    [56, 204, 108],
+    [56, 180, 204],
+    [60, 60, 60],
+    [0, 0, 0],
+    [0, 0, 0],
+    [236, 238, 236],
+    [168, 204, 236],
+    [188, 188, 236],
+    [212, 178, 236],
+    [236, 174, 236],
+    [236, 174, 212],
+    [236, 180, 176],
+    [228, 196, 144],
+    [204, 210, 120],
+    [180, 222, 120],
+    [168, 226, 144],
+    [152, 226, 180],
+    [160, 214, 228],
+    [160, 162, 160],
+    [0, 0, 0],
+    [0, 0, 0],
+];
+
+// Maps the PPU's output to RGB. There are 512 colours, the 64 palette colours under each of the
+// 8 combinations of the PPUMASK emphasis bits, in the order emphasis * 64 + colour
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<[u8; 3]>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::with_emphasis(&NTSC_2C02)
+    }
+}
+
+impl Palette {
+    // Loads a .pal file, either the 64 base colours or all 512 colours with emphasis, as 3
+    // bytes of RGB each
+    pub fn from_pal(data: &[u8]) -> Result<Self, NesError> {
+        let colors: Vec<[u8; 3]> = data
+            .chunks_exact(3)
+            .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+            .collect();
+        match data.len() {
+            192 => Ok(Palette::with_emphasis(&colors)),
+            1536 => Ok(Palette { colors }),
+            length => Err(NesError::InvalidPalette(length)),
+        }
+    }
+
+    // Emphasis darkens the channels that are not emphasised to about 80%. The black columns
+    // $xE and $xF are not affected
+    fn with_emphasis(base: &[[u8; 3]]) -> Self {
+        let mut colors = Vec::with_capacity(512);
+        for emphasis in 0..8 {
+            for (index, rgb) in base.iter().enumerate() {
+                let mut rgb = *rgb;
+                if emphasis != 0 && index & 0x0E != 0x0E {
+                    for (channel, value) in rgb.iter_mut().enumerate() {
+                        if emphasis & (1 << channel) == 0 {
+                            *value = (*value as u32 * 816 / 1000) as u8;
+                        }
+                    }
+                }
+                colors.push(rgb);
+            }
+        }
+        Palette { colors }
+    }
+
+    // Takes a colour from the frame buffer, the palette colour in bits 0-5 and the emphasis
+    // bits in 6-8
+    pub fn rgb(&self, color: u16) -> [u8; 3] {
+        self.colors[(color & 0x1FF) as usize]
+    }
+
+    // Converts a frame buffer to 3 bytes of RGB per pixel
+    pub fn to_rgb(&self, frame: &[u16]) -> Vec<u8> {
+        frame.iter().flat_map(|&color| self.rgb(color)).collect()
+    }
+}
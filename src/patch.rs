@@ -0,0 +1,349 @@
+use crate::error::NesError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchFormat {
+    Ips,
+    Ups,
+    Bps,
+}
+
+impl PatchFormat {
+    pub fn detect(patch: &[u8]) -> Option<Self> {
+        if patch.starts_with(b"PATCH") {
+            Some(PatchFormat::Ips)
+        } else if patch.starts_with(b"UPS1") {
+            Some(PatchFormat::Ups)
+        } else if patch.starts_with(b"BPS1") {
+            Some(PatchFormat::Bps)
+        } else {
+            None
+        }
+    }
+}
+
+// Applies an IPS, UPS or BPS patch to a whole ROM image, header included
+pub fn apply_patch(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, NesError> {
+    match PatchFormat::detect(patch) {
+        Some(PatchFormat::Ips) => apply_ips(source, patch),
+        Some(PatchFormat::Ups) => apply_ups(source, patch),
+        Some(PatchFormat::Bps) => apply_bps(source, patch),
+        None => Err(NesError::UnknownPatchFormat),
+    }
+}
+
+// Reads the patch front to back, failing on a truncated patch
+struct PatchReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Self {
+        PatchReader { data, offset }
+    }
+
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], NesError> {
+        let end = self
+            .offset
+            .checked_add(length)
+            .ok_or(NesError::CorruptPatch)?;
+        let bytes = self
+            .data
+            .get(self.offset..end)
+            .ok_or(NesError::CorruptPatch)?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, NesError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn big_endian(&mut self, length: usize) -> Result<usize, NesError> {
+        Ok(self
+            .bytes(length)?
+            .iter()
+            .fold(0, |value, &byte| value << 8 | byte as usize))
+    }
+
+    // UPS and BPS store numbers 7 bits at a time, with bit 7 marking the last byte. Each
+    // continuation also adds one so every value has a single encoding
+    fn number(&mut self) -> Result<usize, NesError> {
+        let mut value: usize = 0;
+        let mut shift: usize = 1;
+        loop {
+            let byte = self.byte()?;
+            value = (byte as usize & 0x7F)
+                .checked_mul(shift)
+                .and_then(|part| value.checked_add(part))
+                .ok_or(NesError::CorruptPatch)?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift.checked_mul(128).ok_or(NesError::CorruptPatch)?;
+            value = value.checked_add(shift).ok_or(NesError::CorruptPatch)?;
+        }
+    }
+}
+
+// IPS is a list of records that each overwrite a run of bytes, ending with "EOF" and an
+// optional 3 byte length to truncate the output to
+fn apply_ips(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, NesError> {
+    let mut target = source.to_vec();
+    let mut reader = PatchReader::new(patch, 5);
+
+    loop {
+        let offset_bytes = reader.bytes(3)?;
+        if offset_bytes == b"EOF" {
+            break;
+        }
+        let offset = offset_bytes
+            .iter()
+            .fold(0, |value, &byte| value << 8 | byte as usize);
+
+        let length = reader.big_endian(2)?;
+        let data = if length == 0 {
+            // Run length encoded record
+            let run_length = reader.big_endian(2)?;
+            vec![reader.byte()?; run_length]
+        } else {
+            reader.bytes(length)?.to_vec()
+        };
+
+        if target.len() < offset + data.len() {
+            target.resize(offset + data.len(), 0);
+        }
+        target[offset..offset + data.len()].copy_from_slice(&data);
+    }
+
+    if let Ok(length) = reader.big_endian(3) {
+        target.truncate(length);
+    }
+    Ok(target)
+}
+
+// The most a UPS or BPS target may grow beyond the source and patch combined. Runs of bytes
+// that are already right can be left out of a patch, but a header claiming a huge target would
+// otherwise be allocated before any of it is checked
+const MAX_TARGET_GROWTH: usize = 16 * 1024 * 1024;
+
+fn check_target_size(target_size: usize, source: &[u8], patch: &[u8]) -> Result<(), NesError> {
+    let limit = source.len() + patch.len() + MAX_TARGET_GROWTH;
+    if target_size > limit {
+        return Err(NesError::CorruptPatch);
+    }
+    Ok(())
+}
+
+// UPS and BPS end with the CRC32s of the source, the target and the rest of the patch
+fn checksums(patch: &[u8]) -> Result<(u32, u32), NesError> {
+    if patch.len() < 16 {
+        return Err(NesError::CorruptPatch);
+    }
+    let footer = &patch[patch.len() - 12..];
+    let read_crc = |offset: usize| {
+        u32::from_le_bytes([
+            footer[offset],
+            footer[offset + 1],
+            footer[offset + 2],
+            footer[offset + 3],
+        ])
+    };
+
+    verify_checksum("patch", read_crc(8), &patch[..patch.len() - 4])?;
+    Ok((read_crc(0), read_crc(4)))
+}
+
+fn verify_checksum(checksum: &'static str, expected: u32, data: &[u8]) -> Result<(), NesError> {
+    let actual = crc32fast::hash(data);
+    if actual != expected {
+        return Err(NesError::PatchChecksumMismatch {
+            checksum,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+// UPS XORs runs of the target against the source, each run ending on a zero byte
+fn apply_ups(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, NesError> {
+    let (source_crc, target_crc) = checksums(patch)?;
+    let mut reader = PatchReader::new(&patch[..patch.len() - 12], 4);
+
+    let source_size = reader.number()?;
+    let target_size = reader.number()?;
+    if source_size != source.len() {
+        return Err(NesError::CorruptPatch);
+    }
+    check_target_size(target_size, source, patch)?;
+    verify_checksum("source", source_crc, source)?;
+
+    let mut target = source.to_vec();
+    target.resize(target_size, 0);
+    let mut position: usize = 0;
+
+    while reader.offset < reader.data.len() {
+        position = position
+            .checked_add(reader.number()?)
+            .ok_or(NesError::CorruptPatch)?;
+        loop {
+            let value = reader.byte()?;
+            if position < target_size {
+                target[position] ^= value;
+            }
+            position += 1;
+            if value == 0 {
+                break;
+            }
+        }
+    }
+
+    verify_checksum("target", target_crc, &target)?;
+    Ok(target)
+}
+
+// BPS builds the target from commands that copy from the source, from the patch, or from
+// earlier in the target
+fn apply_bps(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, NesError> {
+    let (source_crc, target_crc) = checksums(patch)?;
+    let mut reader = PatchReader::new(&patch[..patch.len() - 12], 4);
+
+    let source_size = reader.number()?;
+    let target_size = reader.number()?;
+    let metadata_size = reader.number()?;
+    reader.bytes(metadata_size)?;
+    if source_size != source.len() {
+        return Err(NesError::CorruptPatch);
+    }
+    check_target_size(target_size, source, patch)?;
+    verify_checksum("source", source_crc, source)?;
+
+    let mut target: Vec<u8> = Vec::new();
+    let mut source_offset: usize = 0;
+    let mut target_offset: usize = 0;
+
+    while reader.offset < reader.data.len() {
+        let data = reader.number()?;
+        let length = (data >> 2) + 1;
+        if target.len() + length > target_size {
+            return Err(NesError::CorruptPatch);
+        }
+
+        match data & 0x03 {
+            // Source read, copy from the same position in the source
+            0 => {
+                let start = target.len();
+                let bytes = source
+                    .get(start..start + length)
+                    .ok_or(NesError::CorruptPatch)?;
+                target.extend_from_slice(bytes);
+            }
+            // Target read, copy from the patch
+            1 => target.extend_from_slice(reader.bytes(length)?),
+            // Source copy, copy from a relative position in the source
+            2 => {
+                source_offset = relative_offset(source_offset, reader.number()?)?;
+                let end = source_offset
+                    .checked_add(length)
+                    .ok_or(NesError::CorruptPatch)?;
+                let bytes = source
+                    .get(source_offset..end)
+                    .ok_or(NesError::CorruptPatch)?;
+                target.extend_from_slice(bytes);
+                source_offset = end;
+            }
+            // Target copy, copy from earlier in the target one byte at a time as the runs may
+            // overlap
+            _ => {
+                target_offset = relative_offset(target_offset, reader.number()?)?;
+                for _ in 0..length {
+                    let value = *target.get(target_offset).ok_or(NesError::CorruptPatch)?;
+                    target.push(value);
+                    target_offset = target_offset.checked_add(1).ok_or(NesError::CorruptPatch)?;
+                }
+            }
+        }
+    }
+
+    if target.len() != target_size {
+        return Err(NesError::CorruptPatch);
+    }
+    verify_checksum("target", target_crc, &target)?;
+    Ok(target)
+}
+
+// Copy offsets are signed, with the sign in bit 0
+fn relative_offset(offset: usize, data: usize) -> Result<usize, NesError> {
+    let distance = data >> 1;
+    if data & 0x01 != 0 {
+        offset.checked_sub(distance)
+    } else {
+        offset.checked_add(distance)
+    }
+    .ok_or(NesError::CorruptPatch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_number(mut value: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte | 0x80);
+                return bytes;
+            }
+            bytes.push(byte);
+            value -= 1;
+        }
+    }
+
+    // Wraps the body of a UPS or BPS patch with its header sizes and valid checksums
+    fn build_patch(magic: &[u8], sizes: &[usize], body: &[u8], source: &[u8]) -> Vec<u8> {
+        let mut patch = magic.to_vec();
+        for &size in sizes {
+            patch.extend(encode_number(size));
+        }
+        patch.extend_from_slice(body);
+        patch.extend(crc32fast::hash(source).to_le_bytes());
+        patch.extend(0u32.to_le_bytes());
+        patch.extend(crc32fast::hash(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn number_round_trips() {
+        for value in [0, 1, 127, 128, 16511, 16512, 1 << 40] {
+            let encoded = encode_number(value);
+            assert_eq!(PatchReader::new(&encoded, 0).number(), Ok(value));
+        }
+    }
+
+    #[test]
+    fn ups_rejects_huge_target_before_allocating() {
+        let source = [0u8; 16];
+        let patch = build_patch(b"UPS1", &[source.len(), 1 << 50], &[], &source);
+        assert_eq!(apply_patch(&source, &patch), Err(NesError::CorruptPatch));
+    }
+
+    #[test]
+    fn bps_rejects_huge_target_before_allocating() {
+        let source = [0u8; 16];
+        let patch = build_patch(b"BPS1", &[source.len(), 1 << 50, 0], &[], &source);
+        assert_eq!(apply_patch(&source, &patch), Err(NesError::CorruptPatch));
+    }
+
+    #[test]
+    fn bps_rejects_source_copy_past_the_end() {
+        let source = [0u8; 16];
+        // A 4 byte source copy from as far forward as the offset can go
+        let mut body = encode_number(3 << 2 | 2);
+        body.extend(encode_number(usize::MAX & !1));
+        let patch = build_patch(b"BPS1", &[source.len(), 4, 0], &body, &source);
+        assert_eq!(apply_patch(&source, &patch), Err(NesError::CorruptPatch));
+    }
+}
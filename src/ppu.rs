@@ -1,47 +1,629 @@
-use crate::cartridge::CartridgeInfo;
 use crate::cartridge::Mirroring::FourScreen;
 use crate::cartridge::Mirroring::Horizontal;
+use crate::cartridge::Mirroring::SingleScreenA;
+use crate::cartridge::Mirroring::SingleScreenB;
 use crate::cartridge::Mirroring::Vertical;
+use crate::mappers::SharedMapper;
+use crate::nes::Region;
+use crate::palette::Palette;
+
+// A sprite fetched for the scanline being drawn, with its pattern bytes already flipped
+#[derive(Debug, Clone, Copy, Default)]
+struct SpriteSlot {
+    x: u8,
+    attributes: u8,
+    pattern_low: u8,
+    pattern_high: u8,
+}
+
+// An RGB image drawn by one of the debug views, 3 bytes per pixel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl DebugImage {
+    pub fn new(width: usize, height: usize) -> Self {
+        DebugImage {
+            width,
+            height,
+            pixels: vec![0; width * height * 3],
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        let index = (y * self.width + x) * 3;
+        [
+            self.pixels[index],
+            self.pixels[index + 1],
+            self.pixels[index + 2],
+        ]
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, rgb: [u8; 3]) {
+        let index = (y * self.width + x) * 3;
+        self.pixels[index..index + 3].copy_from_slice(&rgb);
+    }
+}
+
+// Overlays for the nametable viewer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NametableView {
+    // Outlines the 256x240 area the next frame will show, from the scroll registers
+    pub show_viewport: bool,
+    // Darkens the lines between tiles
+    pub show_grid: bool,
+}
 
 pub struct Ppu {
-    cartridge_info: CartridgeInfo,
-    chr_memory: Vec<u8>,
-    chr_is_ram: bool,
-    vram: [u8; 2048],
+    mapper: SharedMapper,
+    // 2 KB of console VRAM, followed by the 2 KB that four-screen boards add on the cartridge
+    vram: [u8; 4096],
     palette_ram: [u8; 32],
     oam: [u8; 256],
-    internal_data_buf: u8,
+    oam_address: u8,
+    // Sprites in range of the next scanline, 4 OAM bytes each. Hardware has room for 8
+    secondary_oam: [u8; 256],
+    secondary_oam_count: usize,
+    sprite_zero_in_secondary: bool,
+    sprites: [SpriteSlot; 64],
+    sprite_count: usize,
+    sprite_zero_on_line: bool,
+    // Hardware only draws 8 sprites per scanline, turning this off stops them flickering
+    pub sprite_limit: bool,
+    // Turns the frame buffer into RGB, the 2C02 colours unless a .pal file is loaded
+    pub palette: Palette,
+    // The value last driven onto the data bus between the CPU and the PPU registers, which
+    // reads of write-only registers and unused status bits return
+    io_latch: u8,
+    // The loopy registers, v is the current VRAM address, t the address the next frame or
+    // scanline starts from and x the fine X scroll. w picks between the two writes to
+    // PPUSCROLL and PPUADDR
     write_latch: bool,
     vram_address: u16,
     temporary_vram_address: u16,
-    transfer_address: u16,
+    fine_x: u8,
     vram_increment_32: bool,
+    sprite_pattern_table: u16,
+    background_pattern_table: u16,
+    tall_sprites: bool,
+    mask: u8,
     read_buffer: u8,
+    nmi_enabled: bool,
+    vblank: bool,
+    // Set by reading PPUSTATUS just before vblank starts, which stops the flag being raised
+    suppress_vblank: bool,
+    sprite_zero_hit: bool,
+    sprite_overflow: bool,
+    // Background fetch latches, and the shift registers they are loaded into every 8 dots.
+    // The attribute shifters are fed from the palette latch so they line up with the tiles
+    nametable_latch: u8,
+    attribute_latch: u8,
+    pattern_low_latch: u8,
+    pattern_high_latch: u8,
+    pattern_low_shifter: u16,
+    pattern_high_shifter: u16,
+    attribute_low_shifter: u16,
+    attribute_high_shifter: u16,
+    // 256x240 colours, finished when vblank starts
+    frame_buffer: Vec<u16>,
+    region: Region,
+    scanline: u16,
+    dot: u16,
+    frame: u64,
+    cycles: u64,
 }
 
 impl Ppu {
-    pub fn new(cartridge_info: CartridgeInfo, chr_rom: Vec<u8>) -> Self {
-        let (chr_memory, chr_is_ram) = if chr_rom.is_empty() {
-            (vec![0; 8192], true)
-        } else {
-            (chr_rom, false)
-        };
-
+    pub fn new(mapper: SharedMapper, region: Region) -> Self {
         Ppu {
-            cartridge_info,
-            chr_memory,
-            chr_is_ram,
-            vram: [0; 2048],
+            mapper,
+            vram: [0; 4096],
             palette_ram: [0; 32],
             oam: [0; 256],
-            internal_data_buf: 0,
+            oam_address: 0,
+            secondary_oam: [0xFF; 256],
+            secondary_oam_count: 0,
+            sprite_zero_in_secondary: false,
+            sprites: [SpriteSlot::default(); 64],
+            sprite_count: 0,
+            sprite_zero_on_line: false,
+            sprite_limit: true,
+            palette: Palette::default(),
+            io_latch: 0,
             write_latch: false,
             vram_address: 0,
             temporary_vram_address: 0,
-            transfer_address: 0,
+            fine_x: 0,
             vram_increment_32: false,
+            sprite_pattern_table: 0,
+            background_pattern_table: 0,
+            tall_sprites: false,
+            mask: 0,
             read_buffer: 0,
+            nmi_enabled: false,
+            vblank: false,
+            suppress_vblank: false,
+            sprite_zero_hit: false,
+            sprite_overflow: false,
+            nametable_latch: 0,
+            attribute_latch: 0,
+            pattern_low_latch: 0,
+            pattern_high_latch: 0,
+            pattern_low_shifter: 0,
+            pattern_high_shifter: 0,
+            attribute_low_shifter: 0,
+            attribute_high_shifter: 0,
+            frame_buffer: vec![0; 256 * 240],
+            region,
+            scanline: 0,
+            dot: 0,
+            frame: 0,
+            cycles: 0,
+        }
+    }
+
+    pub fn scanline(&self) -> u16 {
+        self.scanline
+    }
+
+    pub fn dot(&self) -> u16 {
+        self.dot
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    // The last complete frame, 256 pixels wide and 240 high. Each pixel is a 6 bit palette
+    // colour with the PPUMASK emphasis bits above it
+    pub fn frame_buffer(&self) -> &[u16] {
+        &self.frame_buffer
+    }
+
+    // The last complete frame as 3 bytes of RGB per pixel
+    pub fn frame_rgb(&self) -> Vec<u8> {
+        self.palette.to_rgb(&self.frame_buffer)
+    }
+
+    // Advances one dot. Scanlines 0-239 are visible, vblank starts on scanline 241 (291 on
+    // Dendy) and the last scanline of the frame is the pre-render line
+    pub fn tick(&mut self) {
+        self.cycles += 1;
+        self.dot += 1;
+
+        // On NTSC the pre-render line is one dot shorter on odd frames while rendering
+        let skip_dot = self.dot == 340
+            && self.scanline == self.pre_render_line()
+            && self.frame % 2 == 1
+            && self.rendering_enabled()
+            && self.region == Region::Ntsc;
+        if self.dot > 340 || skip_dot {
+            self.dot = 0;
+            self.scanline += 1;
+            if self.scanline >= self.region.scanlines_per_frame() {
+                self.scanline = 0;
+                self.frame += 1;
+            }
+        }
+
+        if self.dot == 1 {
+            if self.scanline == self.region.vblank_scanline() {
+                self.vblank = !self.suppress_vblank;
+                self.suppress_vblank = false;
+            } else if self.scanline == self.pre_render_line() {
+                self.vblank = false;
+                self.sprite_zero_hit = false;
+                self.sprite_overflow = false;
+            }
+        }
+
+        if self.scanline < 240 || self.scanline == self.pre_render_line() {
+            self.render_dot();
+        }
+    }
+
+    fn pre_render_line(&self) -> u16 {
+        self.region.scanlines_per_frame() - 1
+    }
+
+    // PPUMASK bits 3 and 4 show the background and sprites
+    fn rendering_enabled(&self) -> bool {
+        self.mask & 0x18 != 0
+    }
+
+    // Rendering is only happening on the visible and pre-render lines with it enabled
+    fn is_rendering(&self) -> bool {
+        self.rendering_enabled() && (self.scanline < 240 || self.scanline == self.pre_render_line())
+    }
+
+    // One dot of a visible or pre-render line. Every 8 dots the PPU fetches a nametable byte,
+    // an attribute byte and two pattern bytes for the tile after next, while the tile being
+    // drawn shifts out of the shift registers a pixel at a time
+    fn render_dot(&mut self) {
+        if self.rendering_enabled() {
+            self.fetch_dot();
+        }
+
+        // The pixel comes out after the shift, so dot 1 draws the first tile loaded on the line
+        // before
+        if self.scanline < 240 && (1..=256).contains(&self.dot) {
+            self.output_pixel();
+        }
+
+        if !self.rendering_enabled() {
+            return;
+        }
+
+        match self.dot {
+            256 => self.increment_y(),
+            // Copy the horizontal scroll from t, and find the sprites for the next line
+            257 => {
+                self.vram_address =
+                    (self.vram_address & !0x041F) | (self.temporary_vram_address & 0x041F);
+                self.evaluate_sprites();
+            }
+            // Copy the vertical scroll from t, ready for the next frame
+            280..=304 if self.scanline == self.pre_render_line() => {
+                self.vram_address =
+                    (self.vram_address & !0x7BE0) | (self.temporary_vram_address & 0x7BE0);
+            }
+            _ => {}
+        }
+    }
+
+    // The memory fetches and background shifts of one dot while rendering is enabled
+    fn fetch_dot(&mut self) {
+        match self.dot {
+            2..=257 | 321..=337 => {
+                self.shift_background();
+                match (self.dot - 1) % 8 {
+                    0 => {
+                        self.load_background_shifters();
+                        self.nametable_latch = self.fetch(0x2000 | (self.vram_address & 0x0FFF));
+                    }
+                    2 => self.fetch_attribute(),
+                    4 => {
+                        let address = self.background_pattern_address();
+                        self.pattern_low_latch = self.fetch(address);
+                    }
+                    6 => {
+                        let address = self.background_pattern_address() + 8;
+                        self.pattern_high_latch = self.fetch(address);
+                    }
+                    7 => self.increment_coarse_x(),
+                    _ => {}
+                }
+            }
+            258..=320 => self.fetch_sprite_patterns(),
+            // Unused nametable fetches at the end of the line
+            338 | 340 => {
+                self.fetch(0x2000 | (self.vram_address & 0x0FFF));
+            }
+            _ => {}
+        }
+    }
+
+    fn sprite_height(&self) -> u16 {
+        if self.tall_sprites { 16 } else { 8 }
+    }
+
+    // The sprite row on this scanline for an OAM Y coordinate, if the sprite covers it.
+    // Sprites are drawn one line below their Y coordinate
+    fn sprite_row(&self, y: u8) -> Option<u16> {
+        let row = self.scanline.wrapping_sub(y as u16);
+        (row < self.sprite_height()).then_some(row)
+    }
+
+    // Copies the sprites in range of the next scanline into secondary OAM. No sprites are
+    // drawn on the line after the pre-render line
+    fn evaluate_sprites(&mut self) {
+        self.secondary_oam_count = 0;
+        self.sprite_zero_in_secondary = false;
+        if self.scanline == self.pre_render_line() {
+            return;
+        }
+
+        let limit = if self.sprite_limit { 8 } else { 64 };
+        let mut eighth_sprite = None;
+        for n in 0..64 {
+            if self.sprite_row(self.oam[n * 4]).is_none() {
+                continue;
+            }
+            if self.secondary_oam_count == limit {
+                break;
+            }
+            let start = self.secondary_oam_count * 4;
+            self.secondary_oam[start..start + 4].copy_from_slice(&self.oam[n * 4..n * 4 + 4]);
+            self.secondary_oam_count += 1;
+            self.sprite_zero_in_secondary |= n == 0;
+            if self.secondary_oam_count == 8 {
+                eighth_sprite = Some(n);
+            }
+        }
+
+        // Once 8 sprites are found the hardware keeps looking for a ninth to set the overflow
+        // flag, but increments the byte within each sprite along with the sprite number, so
+        // it checks tile numbers, attributes and X coordinates as if they were Y coordinates
+        if let Some(eighth_sprite) = eighth_sprite {
+            let mut byte = 0;
+            for n in eighth_sprite + 1..64 {
+                if self.sprite_row(self.oam[n * 4 + byte]).is_some() {
+                    self.sprite_overflow = true;
+                    break;
+                }
+                byte = (byte + 1) & 0x03;
+            }
+        }
+    }
+
+    // Pattern table address of a sprite row, after vertical flipping. 8x16 sprites take their
+    // pattern table from bit 0 of the tile number
+    fn sprite_pattern_address(&self, sprite: &[u8]) -> u16 {
+        let row = self.sprite_row(sprite[0]).unwrap_or(0);
+        self.sprite_tile_address(sprite[1] as u16, sprite[2], row)
+    }
+
+    fn sprite_tile_address(&self, tile: u16, attributes: u8, mut row: u16) -> u16 {
+        let height = self.sprite_height();
+        if attributes & 0x80 != 0 {
+            row = height - 1 - row;
+        }
+
+        if self.tall_sprites {
+            let table = (tile & 0x01) * 0x1000;
+            let tile = (tile & 0xFE) + row / 8;
+            table + tile * 16 + (row & 0x07)
+        } else {
+            self.sprite_pattern_table + tile * 16 + row
+        }
+    }
+
+    // Fetches the patterns of the sprites in secondary OAM on dots 257-320, 8 dots per sprite.
+    // Empty slots still fetch tile $FF so mappers watching the PPU bus see every access
+    fn fetch_sprite_patterns(&mut self) {
+        // Sprite evaluation leaves OAMADDR at 0
+        self.oam_address = 0;
+
+        let slot = ((self.dot - 257) / 8) as usize;
+        let sprite = if slot < self.secondary_oam_count {
+            let mut sprite = [0; 4];
+            sprite.copy_from_slice(&self.secondary_oam[slot * 4..slot * 4 + 4]);
+            sprite
+        } else {
+            [0xFF; 4]
+        };
+        let address = self.sprite_pattern_address(&sprite);
+        let flip_horizontal = sprite[2] & 0x40 != 0;
+        let flip = |pattern: u8| {
+            if flip_horizontal {
+                pattern.reverse_bits()
+            } else {
+                pattern
+            }
+        };
+
+        match (self.dot - 257) % 8 {
+            0 | 2 => {
+                // Unused nametable fetches
+                self.fetch(0x2000 | (self.vram_address & 0x0FFF));
+            }
+            4 => {
+                let pattern = self.fetch(address);
+                self.sprites[slot] = SpriteSlot {
+                    x: sprite[3],
+                    attributes: sprite[2],
+                    pattern_low: flip(pattern),
+                    pattern_high: 0,
+                };
+            }
+            6 => {
+                let pattern = self.fetch(address + 8);
+                self.sprites[slot].pattern_high = flip(pattern);
+            }
+            _ => {}
+        }
+
+        if self.dot == 320 {
+            self.sprite_count = self.secondary_oam_count;
+            self.sprite_zero_on_line = self.sprite_zero_in_secondary;
+            // Without the sprite limit, sprites past the eighth are read outside the fetch
+            // schedule
+            for slot in 8..self.secondary_oam_count {
+                let mut sprite = [0; 4];
+                sprite.copy_from_slice(&self.secondary_oam[slot * 4..slot * 4 + 4]);
+                let address = self.sprite_pattern_address(&sprite);
+                let pattern_low = self.read_memory(address);
+                let pattern_high = self.read_memory(address + 8);
+                let (pattern_low, pattern_high) = if sprite[2] & 0x40 != 0 {
+                    (pattern_low.reverse_bits(), pattern_high.reverse_bits())
+                } else {
+                    (pattern_low, pattern_high)
+                };
+                self.sprites[slot] = SpriteSlot {
+                    x: sprite[3],
+                    attributes: sprite[2],
+                    pattern_low,
+                    pattern_high,
+                };
+            }
+        }
+    }
+
+    // The first opaque sprite pixel at x, as its pixel value, palette, whether it is behind
+    // the background and whether it is sprite 0
+    fn sprite_pixel(&self, x: usize) -> Option<(u16, u16, bool, bool)> {
+        if self.mask & 0x10 == 0 || (x < 8 && self.mask & 0x04 == 0) {
+            return None;
+        }
+
+        for (index, sprite) in self.sprites[..self.sprite_count].iter().enumerate() {
+            let offset = x.wrapping_sub(sprite.x as usize);
+            if offset >= 8 {
+                continue;
+            }
+            let bit = 7 - offset;
+            let pixel = ((sprite.pattern_low >> bit) & 0x01) as u16
+                | (((sprite.pattern_high >> bit) & 0x01) as u16) << 1;
+            if pixel != 0 {
+                return Some((
+                    pixel,
+                    4 + (sprite.attributes & 0x03) as u16,
+                    sprite.attributes & 0x20 != 0,
+                    index == 0 && self.sprite_zero_on_line,
+                ));
+            }
+        }
+        None
+    }
+
+    // Reads from the PPU bus during rendering, where the cartridge sees the address
+    fn fetch(&mut self, address: u16) -> u8 {
+        self.mapper.borrow_mut().ppu_address(address);
+        self.read_memory(address)
+    }
+
+    fn fetch_attribute(&mut self) {
+        let v = self.vram_address;
+        let address = 0x23C0 | (v & 0x0C00) | ((v >> 4) & 0x38) | ((v >> 2) & 0x07);
+        let attribute = self.fetch(address);
+        // Each attribute byte covers 4x4 tiles, with 2 bits for each 2x2 quadrant
+        let shift = ((v >> 4) & 0x04) | (v & 0x02);
+        self.attribute_latch = (attribute >> shift) & 0x03;
+    }
+
+    fn background_pattern_address(&self) -> u16 {
+        let fine_y = (self.vram_address >> 12) & 0x07;
+        self.background_pattern_table + self.nametable_latch as u16 * 16 + fine_y
+    }
+
+    fn load_background_shifters(&mut self) {
+        self.pattern_low_shifter =
+            (self.pattern_low_shifter & 0xFF00) | self.pattern_low_latch as u16;
+        self.pattern_high_shifter =
+            (self.pattern_high_shifter & 0xFF00) | self.pattern_high_latch as u16;
+        let low = if self.attribute_latch & 0x01 != 0 {
+            0xFF
+        } else {
+            0x00
+        };
+        let high = if self.attribute_latch & 0x02 != 0 {
+            0xFF
+        } else {
+            0x00
+        };
+        self.attribute_low_shifter = (self.attribute_low_shifter & 0xFF00) | low;
+        self.attribute_high_shifter = (self.attribute_high_shifter & 0xFF00) | high;
+    }
+
+    fn shift_background(&mut self) {
+        self.pattern_low_shifter <<= 1;
+        self.pattern_high_shifter <<= 1;
+        self.attribute_low_shifter <<= 1;
+        self.attribute_high_shifter <<= 1;
+    }
+
+    fn output_pixel(&mut self) {
+        let x = (self.dot - 1) as usize;
+
+        if !self.rendering_enabled() {
+            // With rendering off the backdrop colour is shown, unless v points into the
+            // palettes in which case that colour is
+            let color = if self.vram_address >= 0x3F00 {
+                self.read_memory(self.vram_address)
+            } else {
+                self.read_memory(0x3F00)
+            };
+            self.frame_buffer[self.scanline as usize * 256 + x] = self.output_color(color);
+            return;
+        }
+
+        let (background_pixel, background_palette) =
+            if self.mask & 0x08 != 0 && (x >= 8 || self.mask & 0x02 != 0) {
+                let mask = 0x8000 >> self.fine_x;
+                let pixel = (self.pattern_low_shifter & mask != 0) as u16
+                    | ((self.pattern_high_shifter & mask != 0) as u16) << 1;
+                let palette = (self.attribute_low_shifter & mask != 0) as u16
+                    | ((self.attribute_high_shifter & mask != 0) as u16) << 1;
+                (pixel, palette)
+            } else {
+                (0, 0)
+            };
+
+        let (pixel, palette) = match self.sprite_pixel(x) {
+            Some((sprite_pixel, sprite_palette, behind_background, is_sprite_zero)) => {
+                // Sprite 0 hit needs both pixels opaque, and never happens on the last dot
+                if is_sprite_zero && background_pixel != 0 && x != 255 {
+                    self.sprite_zero_hit = true;
+                }
+                if behind_background && background_pixel != 0 {
+                    (background_pixel, background_palette)
+                } else {
+                    (sprite_pixel, sprite_palette)
+                }
+            }
+            None => (background_pixel, background_palette),
+        };
+
+        let color = if pixel == 0 {
+            self.read_memory(0x3F00)
+        } else {
+            self.read_memory(0x3F00 | palette << 2 | pixel)
+        };
+        self.frame_buffer[self.scanline as usize * 256 + x] = self.output_color(color);
+    }
+
+    // Grayscale keeps only the brightness column of the colour, and the emphasis bits are
+    // stored above it. The PAL PPU swaps the red and green emphasis bits
+    fn output_color(&self, color: u8) -> u16 {
+        let color = if self.mask & 0x01 != 0 {
+            color & 0x30
+        } else {
+            color & 0x3F
+        };
+        let emphasis = match self.region {
+            Region::Pal => {
+                (self.mask >> 5 & 0x04) | (self.mask >> 6 & 0x01) | (self.mask >> 4 & 0x02)
+            }
+            Region::Ntsc | Region::Dendy => self.mask >> 5,
+        };
+        (emphasis as u16) << 6 | color as u16
+    }
+
+    // Moves v to the next tile, wrapping into the horizontally adjacent nametable
+    fn increment_coarse_x(&mut self) {
+        if self.vram_address & 0x001F == 31 {
+            self.vram_address &= !0x001F;
+            self.vram_address ^= 0x0400;
+        } else {
+            self.vram_address += 1;
+        }
+    }
+
+    // Moves v down a pixel row. Coarse Y wraps into the vertically adjacent nametable after
+    // row 29, but rows 30 and 31 (attribute memory) wrap without switching
+    fn increment_y(&mut self) {
+        if self.vram_address & 0x7000 != 0x7000 {
+            self.vram_address += 0x1000;
+            return;
+        }
+        self.vram_address &= !0x7000;
+        let mut coarse_y = (self.vram_address & 0x03E0) >> 5;
+        if coarse_y == 29 {
+            coarse_y = 0;
+            self.vram_address ^= 0x0800;
+        } else if coarse_y == 31 {
+            coarse_y = 0;
+        } else {
+            coarse_y += 1;
         }
+        self.vram_address = (self.vram_address & !0x03E0) | coarse_y << 5;
     }
 
     pub fn debug_draw_pattern_tables(&self) -> Vec<u8> {
@@ -49,7 +631,7 @@ impl Ppu {
         let height = 128;
         let mut frame_buffer = vec![0; width * height * 3];
 
-        let palette = [(0, 0, 0), (85, 85, 85), (170, 170, 170), (255, 255, 255)];
+        let mapper = self.mapper.borrow();
 
         for table in 0..2 {
             for tile_y in 0..16 {
@@ -57,13 +639,9 @@ impl Ppu {
                     let tile_n = tile_y * 16 + tile_x;
                     let offset = table * 4096 + tile_n * 16;
 
-                    if offset + 16 > self.chr_memory.len() {
-                        continue;
-                    }
-
                     for row in 0..8 {
-                        let tile_lsb = self.chr_memory[offset + row];
-                        let tile_msb = self.chr_memory[offset + row + 8];
+                        let tile_lsb = mapper.ppu_read((offset + row) as u16);
+                        let tile_msb = mapper.ppu_read((offset + row + 8) as u16);
 
                         for col in 0..8 {
                             let mask = 1 << (7 - col);
@@ -71,7 +649,7 @@ impl Ppu {
                             let msb = (tile_msb & mask) != 0;
 
                             let val = (if msb { 2 } else { 0 }) | (if lsb { 1 } else { 0 });
-                            let (r, g, b) = palette[val];
+                            let [r, g, b] = self.debug_color(0, val);
 
                             let pixel_x = table * 128 + tile_x * 8 + col;
                             let pixel_y = tile_y * 8 + row;
@@ -88,158 +666,546 @@ impl Ppu {
         frame_buffer
     }
 
-    pub fn debug_draw_nametable(&self) -> Vec<u8> {
-        // Draw 2 nametables side by side
-        // Each nametable is 256x240 pixels (32x30 tiles of 8x8 pixels)
-        let width = 512;
-        let height = 240;
-        let mut frame_buffer = vec![0; width * height * 3];
-
-        let palette = [(0, 0, 0), (85, 85, 85), (170, 170, 170), (255, 255, 255)];
+    // Draws the four nametables in a 2x2 grid as they would be rendered, with the attribute
+    // palettes and the background pattern table from PPUCTRL
+    pub fn debug_draw_nametables(&self, options: &NametableView) -> DebugImage {
+        let mut image = DebugImage::new(512, 480);
+        let mapper = self.mapper.borrow();
 
-        for nametable in 0..2u16 {
+        for nametable in 0..4u16 {
             let nametable_base = 0x2000 + nametable * 0x400;
-            let screen_offset_x = nametable as usize * 256;
+            let screen_offset_x = (nametable & 1) as usize * 256;
+            let screen_offset_y = (nametable >> 1) as usize * 240;
 
             for tile_y in 0..30usize {
                 for tile_x in 0..32usize {
-                    // Get tile index from nametable in VRAM
                     let nametable_addr = nametable_base + (tile_y * 32 + tile_x) as u16;
-                    let mapped_addr = self.map_vram_address(nametable_addr);
-                    let tile_index = self.vram[mapped_addr] as usize;
+                    let tile_index = self.read_memory(nametable_addr) as u16;
+                    let chr_offset = self.background_pattern_table | tile_index << 4;
 
-                    // Get tile from pattern table 0
-                    // TODO: Use PPUCTRL bit 4 to select pattern table
-                    let chr_offset = tile_index * 16;
+                    // Each attribute byte covers 4x4 tiles, 2 bits for each 2x2 quarter
+                    let attribute_addr =
+                        nametable_base + 0x3C0 + (tile_y / 4 * 8 + tile_x / 4) as u16;
+                    let shift = (tile_y & 0x02) << 1 | (tile_x & 0x02);
+                    let palette = (self.read_memory(attribute_addr) >> shift & 0x03) as usize;
 
-                    for row in 0..8usize {
-                        if chr_offset + row + 8 >= self.chr_memory.len() {
-                            continue;
-                        }
-
-                        let tile_lsb = self.chr_memory[chr_offset + row];
-                        let tile_msb = self.chr_memory[chr_offset + row + 8];
+                    for row in 0..8u16 {
+                        let tile_lsb = mapper.ppu_read(chr_offset + row);
+                        let tile_msb = mapper.ppu_read(chr_offset + row + 8);
 
                         for col in 0..8usize {
                             let mask = 1 << (7 - col);
                             let lsb = if tile_lsb & mask != 0 { 1 } else { 0 };
                             let msb = if tile_msb & mask != 0 { 2 } else { 0 };
-                            let val = msb | lsb;
-
-                            let (r, g, b) = palette[val];
 
-                            let pixel_x = screen_offset_x + tile_x * 8 + col;
-                            let pixel_y = tile_y * 8 + row;
-
-                            let index = (pixel_y * width + pixel_x) * 3;
-                            frame_buffer[index] = r;
-                            frame_buffer[index + 1] = g;
-                            frame_buffer[index + 2] = b;
+                            image.set_pixel(
+                                screen_offset_x + tile_x * 8 + col,
+                                screen_offset_y + tile_y * 8 + row as usize,
+                                self.debug_color(palette, msb | lsb),
+                            );
                         }
                     }
                 }
             }
         }
 
-        frame_buffer
+        if options.show_grid {
+            for y in 0..image.height {
+                for x in 0..image.width {
+                    if x % 8 == 0 || y % 8 == 0 {
+                        let [r, g, b] = image.pixel(x, y);
+                        image.set_pixel(x, y, [r / 2, g / 2, b / 2]);
+                    }
+                }
+            }
+        }
+
+        if options.show_viewport {
+            // The next frame starts from t and fine X, the viewport wraps around the edges
+            let t = self.temporary_vram_address as usize;
+            let scroll_x = (t & 0x1F) * 8 + self.fine_x as usize + (t >> 10 & 1) * 256;
+            let scroll_y = (t >> 5 & 0x1F) * 8 + (t >> 12 & 0x07) + (t >> 11 & 1) * 240;
+            let color = [255, 0, 0];
+            for x in 0..256 {
+                image.set_pixel((scroll_x + x) % 512, scroll_y % 480, color);
+                image.set_pixel((scroll_x + x) % 512, (scroll_y + 239) % 480, color);
+            }
+            for y in 0..240 {
+                image.set_pixel(scroll_x % 512, (scroll_y + y) % 480, color);
+                image.set_pixel((scroll_x + 255) % 512, (scroll_y + y) % 480, color);
+            }
+        }
+
+        image
     }
 
+    // Draws the 64 sprites in OAM order, 8 to a row, with their palettes and flipping. Each
+    // cell is 8x8 or 8x16 depending on the sprite size, transparent pixels show the backdrop
+    pub fn debug_draw_sprites(&self) -> DebugImage {
+        let height = self.sprite_height() as usize;
+        let mut image = DebugImage::new(64, 8 * height);
+        let mapper = self.mapper.borrow();
+
+        for (n, sprite) in self.oam.chunks_exact(4).enumerate() {
+            let (tile, attributes) = (sprite[1] as u16, sprite[2]);
+            let palette = 4 + (attributes & 0x03) as usize;
+
+            for row in 0..height {
+                let address = self.sprite_tile_address(tile, attributes, row as u16);
+                let tile_lsb = mapper.ppu_read(address);
+                let tile_msb = mapper.ppu_read(address + 8);
+
+                for col in 0..8usize {
+                    let bit = if attributes & 0x40 != 0 { col } else { 7 - col };
+                    let lsb = (tile_lsb >> bit & 1) as usize;
+                    let msb = (tile_msb >> bit & 1) as usize;
+
+                    image.set_pixel(
+                        n % 8 * 8 + col,
+                        n / 8 * height + row,
+                        self.debug_color(palette, msb << 1 | lsb),
+                    );
+                }
+            }
+        }
+        image
+    }
+
+    // Lists the 64 sprites in OAM, one per line
+    pub fn debug_sprite_table(&self) -> String {
+        let mut table = String::from(" #   X   Y  Tile Attr Palette Flags\n");
+        for (n, sprite) in self.oam.chunks_exact(4).enumerate() {
+            let (y, tile, attributes, x) = (sprite[0], sprite[1], sprite[2], sprite[3]);
+            let flags = [(0x20, "behind"), (0x40, "flip-h"), (0x80, "flip-v")]
+                .iter()
+                .filter(|(bit, _)| attributes & bit != 0)
+                .map(|(_, name)| *name)
+                .collect::<Vec<&str>>()
+                .join(" ");
+            let line = format!(
+                "{:2} {:3} {:3}  ${:02X}  ${:02X} {:7} {}",
+                n,
+                x,
+                y,
+                tile,
+                attributes,
+                attributes & 0x03,
+                flags
+            );
+            table.push_str(line.trim_end());
+            table.push('\n');
+        }
+        table
+    }
+
+    // Draws the 32 palette RAM entries as 16x16 swatches, the background palettes on the top
+    // row and the sprite palettes below
+    pub fn debug_draw_palettes(&self) -> DebugImage {
+        let mut image = DebugImage::new(256, 32);
+        for entry in 0..32usize {
+            let color = self.read_memory(0x3F00 + entry as u16);
+            let rgb = self.palette.rgb(color as u16);
+            for y in 0..16 {
+                for x in 0..16 {
+                    image.set_pixel(entry % 16 * 16 + x, entry / 16 * 16 + y, rgb);
+                }
+            }
+        }
+        image
+    }
+
+    // The RGB colour of a pixel value in one of the 4 background palettes or, from 4 up, the 4
+    // sprite palettes, for the debug views
+    fn debug_color(&self, palette: usize, pixel: usize) -> [u8; 3] {
+        let address = if pixel == 0 {
+            0x3F00
+        } else {
+            0x3F00 | (palette << 2 | pixel) as u16
+        };
+        self.palette.rgb(self.read_memory(address) as u16)
+    }
+
+    // Mappers can switch the mirroring at any time, so it is looked up on every access
     fn map_vram_address(&self, addr: u16) -> usize {
         let mirrored_addr = addr & 0x0FFF;
 
-        match self.cartridge_info.mirroring {
+        match self.mapper.borrow().mirroring() {
             Horizontal => ((mirrored_addr & 0x3FF) | ((mirrored_addr >> 1) & 0x400)) as usize,
             Vertical => (mirrored_addr & 0x7FF) as usize,
-            FourScreen => {
-                todo!("FourScreen mirroring");
-            }
+            SingleScreenA => (mirrored_addr & 0x3FF) as usize,
+            SingleScreenB => (0x400 | (mirrored_addr & 0x3FF)) as usize,
+            FourScreen => mirrored_addr as usize,
         }
     }
 
+    // The PPU pulls the CPU's NMI line for as long as it is in vblank with NMI output enabled
+    pub fn nmi_line(&self) -> bool {
+        self.vblank && self.nmi_enabled
+    }
+
+    fn status(&self) -> u8 {
+        (self.vblank as u8) << 7
+            | (self.sprite_zero_hit as u8) << 6
+            | (self.sprite_overflow as u8) << 5
+            | (self.io_latch & 0x1F)
+    }
+
+    // Reads a register without side effects, for tracing and debuggers
+    // The OAM byte at OAMADDR. The unused attribute bits read back as 0
+    fn oam_data(&self) -> u8 {
+        let value = self.oam[self.oam_address as usize];
+        if self.oam_address & 0x03 == 0x02 {
+            value & 0xE3
+        } else {
+            value
+        }
+    }
+
+    // The registers repeat every 8 bytes through $2000-$3FFF
     pub fn peek_register(&self, address: u16) -> u8 {
-        match address {
-            0x2002 => 0x80, // PPU STATUS
+        match 0x2000 | (address & 0x07) {
+            0x2002 => self.status(),
+            0x2004 => self.oam_data(),
+            0x2007 if self.vram_address >= 0x3F00 => self.read_palette(self.vram_address),
             0x2007 => self.read_buffer,
-            _ => 0,
+            _ => self.io_latch,
         }
     }
 
     pub fn read_register(&mut self, address: u16) -> u8 {
-        match address {
-            0x2002 => 0x80, // PPU STATUS
-            0x2007 => {
-                let previous_buffer = self.read_buffer;
-                match self.vram_address {
-                    ..0x2000 => {
-                        self.read_buffer = self.chr_memory[self.vram_address as usize];
-                    }
-                    _ => todo!("Finish read register"),
+        let value = match 0x2000 | (address & 0x07) {
+            0x2002 => {
+                // PPUSTATUS
+                let status = self.status();
+                if self.scanline == self.region.vblank_scanline() && self.dot == 0 {
+                    self.suppress_vblank = true;
                 }
-                self.vram_address =
-                    self.vram_address
-                        .wrapping_add(if self.vram_increment_32 { 32 } else { 1 });
-                self.vram_address &= 0x3FFF;
-                previous_buffer
+                self.vblank = false;
+                self.write_latch = false;
+                status
             }
-            _ => 0,
-        }
+            0x2004 => {
+                // OAMDATA
+                self.oam_data()
+            }
+            0x2007 => {
+                // PPUDATA
+                let address = self.vram_address;
+                self.mapper.borrow_mut().ppu_address(address);
+                let value = if address >= 0x3F00 {
+                    // Palette reads skip the buffer, which picks up the nametable underneath
+                    self.read_buffer = self.read_memory(address & 0x2FFF);
+                    self.read_palette(address)
+                } else {
+                    let previous_buffer = self.read_buffer;
+                    self.read_buffer = self.read_memory(address);
+                    previous_buffer
+                };
+                self.increment_vram_address();
+                value
+            }
+            // Write-only registers
+            _ => self.io_latch,
+        };
+        self.io_latch = value;
+        value
     }
 
     pub fn write_register(&mut self, address: u16, value: u8) {
-        match address {
-            0x2000 => {}
-            0x2001 => {}
+        self.io_latch = value;
+        match 0x2000 | (address & 0x07) {
+            0x2000 => {
+                // PPUCTRL, the low 2 bits select the base nametable
+                self.temporary_vram_address =
+                    (self.temporary_vram_address & !0x0C00) | ((value & 0x03) as u16) << 10;
+                self.vram_increment_32 = value & 0x04 != 0;
+                self.sprite_pattern_table = if value & 0x08 != 0 { 0x1000 } else { 0 };
+                self.background_pattern_table = if value & 0x10 != 0 { 0x1000 } else { 0 };
+                self.tall_sprites = value & 0x20 != 0;
+                self.nmi_enabled = value & 0x80 != 0;
+            }
+            0x2001 => {
+                // PPUMASK
+                self.mask = value;
+            }
             0x2002 => {}
-            0x2003 => {}
-            0x2004 => {}
-            0x2005 => {}
+            0x2003 => {
+                // OAMADDR
+                self.oam_address = value;
+            }
+            0x2004 => {
+                // OAMDATA
+                self.oam[self.oam_address as usize] = value;
+                self.oam_address = self.oam_address.wrapping_add(1);
+            }
+            0x2005 => {
+                // PPUSCROLL
+                self.ppu_scroll(value);
+            }
             0x2006 => {
                 // PPUADDR
                 self.ppu_addr(value);
             }
-            0x2007 => {
+            _ => {
                 // PPUDATA
                 self.ppu_data(value);
             }
-            _ => {
-                todo!("Unimplemented ppu register write 0x{:04X}", address);
-            }
         }
     }
 
+    // Reads the PPU address space, $0000-$1FFF is the cartridge, $2000-$3EFF the nametables
+    // and $3F00-$3FFF the palettes
+    fn read_memory(&self, address: u16) -> u8 {
+        match address {
+            ..0x2000 => self.mapper.borrow().ppu_read(address),
+            0x2000..0x3F00 => self.vram[self.map_vram_address(address)],
+            _ => self.palette_ram[palette_index(address)],
+        }
+    }
+
+    // Palette RAM is 6 bits wide, so the top 2 bits of a $2007 read come from the open bus.
+    // Grayscale applies to reads as well as to the picture
+    fn read_palette(&self, address: u16) -> u8 {
+        let value = self.read_memory(address);
+        let value = if self.mask & 0x01 != 0 {
+            value & 0x30
+        } else {
+            value
+        };
+        value | (self.io_latch & 0xC0)
+    }
+
+    // PPUDATA accesses move v on by 1 or 32, except while rendering where they glitch into
+    // incrementing coarse X and Y together
+    fn increment_vram_address(&mut self) {
+        if self.is_rendering() {
+            self.increment_coarse_x();
+            self.increment_y();
+            return;
+        }
+        self.vram_address =
+            self.vram_address
+                .wrapping_add(if self.vram_increment_32 { 32 } else { 1 })
+                & 0x3FFF;
+    }
+
     pub fn ppu_data(&mut self, value: u8) {
+        self.mapper.borrow_mut().ppu_address(self.vram_address);
         match self.vram_address {
             ..0x2000 => {
-                // If the CHR ROM is 0-length, it can be used as CHR RAM
-                if self.chr_is_ram {
-                    self.chr_memory[self.vram_address as usize] = value;
-                }
+                self.mapper.borrow_mut().ppu_write(self.vram_address, value);
             }
             0x2000..0x3F00 => {
                 let mapped_vram_index = self.map_vram_address(self.vram_address);
                 self.vram[mapped_vram_index] = value;
             }
-            _ => {
-                if (self.vram_address & 0x03) == 0 {
-                    self.palette_ram[(self.vram_address & 0x0F) as usize] = value;
-                } else {
-                    self.palette_ram[(self.vram_address & 0x1F) as usize] = value;
-                }
-            }
+            _ => self.palette_ram[palette_index(self.vram_address)] = value & 0x3F,
         }
-        self.vram_address =
-            self.vram_address
-                .wrapping_add(if self.vram_increment_32 { 32 } else { 1 });
-        self.vram_address &= 0x3FFF;
+        self.increment_vram_address();
+    }
+
+    // The first write sets coarse and fine X, the second coarse and fine Y
+    pub fn ppu_scroll(&mut self, value: u8) {
+        if !self.write_latch {
+            self.temporary_vram_address =
+                (self.temporary_vram_address & !0x001F) | (value >> 3) as u16;
+            self.fine_x = value & 0x07;
+        } else {
+            self.temporary_vram_address = (self.temporary_vram_address & !0x73E0)
+                | ((value & 0x07) as u16) << 12
+                | ((value & 0xF8) as u16) << 2;
+        }
+        self.write_latch = !self.write_latch;
     }
 
+    // The first write sets the high 6 bits of t, the second the low byte before copying t to v
     pub fn ppu_addr(&mut self, value: u8) {
         if !self.write_latch {
-            self.temporary_vram_address = ((value & 0x3F) as u16) << 8;
+            self.temporary_vram_address =
+                (self.temporary_vram_address & 0x00FF) | ((value & 0x3F) as u16) << 8;
         } else {
-            self.vram_address = self.temporary_vram_address | value as u16;
-            self.transfer_address = self.vram_address;
+            self.temporary_vram_address = (self.temporary_vram_address & 0xFF00) | value as u16;
+            self.vram_address = self.temporary_vram_address;
+            // Outside rendering the PPU bus follows the VRAM address
+            self.mapper.borrow_mut().ppu_address(self.vram_address);
         }
         self.write_latch = !self.write_latch;
     }
 }
+
+// Palette RAM repeats every 32 bytes from $3F00. The sprite palettes' first entries, $3F10, $3F14,
+// $3F18 and $3F1C, are the same memory as the background palettes' $3F00, $3F04, $3F08 and $3F0C
+fn palette_index(address: u16) -> usize {
+    let index = (address & 0x1F) as usize;
+    if index & 0x13 == 0x10 {
+        index & 0x0F
+    } else {
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::Mirroring;
+    use crate::mappers::Mapper;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // 8 KB of CHR RAM and fixed mirroring
+    struct TestMapper {
+        chr_ram: Vec<u8>,
+        mirroring: Mirroring,
+    }
+
+    impl Mapper for TestMapper {
+        fn cpu_read(&self, _address: u16) -> Option<u8> {
+            None
+        }
+
+        fn cpu_write(&mut self, _address: u16, _value: u8) -> bool {
+            false
+        }
+
+        fn ppu_read(&self, address: u16) -> u8 {
+            self.chr_ram[address as usize & 0x1FFF]
+        }
+
+        fn ppu_write(&mut self, address: u16, value: u8) {
+            self.chr_ram[address as usize & 0x1FFF] = value;
+        }
+
+        fn mirroring(&self) -> Mirroring {
+            self.mirroring
+        }
+    }
+
+    fn test_ppu(mirroring: Mirroring) -> Ppu {
+        let mapper = TestMapper {
+            chr_ram: vec![0; 8192],
+            mirroring,
+        };
+        Ppu::new(Rc::new(RefCell::new(mapper)), Region::Ntsc)
+    }
+
+    // The 1 KB page of VRAM each of $2000, $2400, $2800 and $2C00 lands in
+    fn vram_pages(mirroring: Mirroring) -> [usize; 4] {
+        let ppu = test_ppu(mirroring);
+        [0x2000, 0x2400, 0x2800, 0x2C00].map(|address| ppu.map_vram_address(address) / 0x400)
+    }
+
+    #[test]
+    fn nametable_mirroring() {
+        assert_eq!(vram_pages(Horizontal), [0, 0, 1, 1]);
+        assert_eq!(vram_pages(Vertical), [0, 1, 0, 1]);
+        assert_eq!(vram_pages(SingleScreenA), [0, 0, 0, 0]);
+        assert_eq!(vram_pages(SingleScreenB), [1, 1, 1, 1]);
+        assert_eq!(vram_pages(FourScreen), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn nametable_mirroring_repeats_at_3000() {
+        let ppu = test_ppu(Vertical);
+        assert_eq!(ppu.map_vram_address(0x3123), ppu.map_vram_address(0x2123));
+        assert_eq!(ppu.map_vram_address(0x27FF), 0x7FF);
+    }
+
+    fn write_vram(ppu: &mut Ppu, address: u16, data: &[u8]) {
+        ppu.write_register(0x2006, (address >> 8) as u8);
+        ppu.write_register(0x2006, address as u8);
+        for &value in data {
+            ppu.write_register(0x2007, value);
+        }
+    }
+
+    // Tiles 1, 2 and 3 are solid in pixel values 1, 2 and 3, and the nametable starts with tiles
+    // 1, 0, 2 and 3. Sprite 0 is a solid tile in the top left corner of the screen
+    fn test_scene() -> Ppu {
+        let mut ppu = test_ppu(Vertical);
+        write_vram(&mut ppu, 0x0010, &[0xFF; 8]);
+        write_vram(&mut ppu, 0x0028, &[0xFF; 8]);
+        write_vram(&mut ppu, 0x0030, &[0xFF; 16]);
+        write_vram(&mut ppu, 0x2000, &[1, 0, 2, 3]);
+        write_vram(&mut ppu, 0x3F00, &[0x0F, 0x01, 0x02, 0x03]);
+        write_vram(&mut ppu, 0x3F11, &[0x11]);
+        ppu.write_register(0x2003, 0);
+        for value in [0, 1, 0, 0] {
+            ppu.write_register(0x2004, value);
+        }
+
+        // Scroll back to the top left and show everything, including the leftmost 8 pixels
+        ppu.write_register(0x2000, 0x00);
+        ppu.write_register(0x2005, 0);
+        ppu.write_register(0x2005, 0);
+        ppu.write_register(0x2001, 0x1E);
+        ppu
+    }
+
+    fn run_until(ppu: &mut Ppu, frame: u64, scanline: u16) {
+        while ppu.frame() < frame || ppu.scanline() != scanline {
+            ppu.tick();
+        }
+    }
+
+    #[test]
+    fn first_tile_of_the_line_is_drawn_at_x_0() {
+        let mut ppu = test_scene();
+        ppu.write_register(0x2001, 0x0A);
+        run_until(&mut ppu, 2, 1);
+
+        let row: Vec<u16> = ppu.frame_buffer()[..32].to_vec();
+        assert_eq!(row[..8], [0x01; 8]);
+        assert_eq!(row[8..16], [0x0F; 8]);
+        assert_eq!(row[16..24], [0x02; 8]);
+        assert_eq!(row[24..32], [0x03; 8]);
+    }
+
+    #[test]
+    fn sprite_zero_hits_in_column_0() {
+        let mut ppu = test_scene();
+        run_until(&mut ppu, 2, 1);
+        assert_eq!(ppu.peek_register(0x2002) & 0x40, 0);
+        run_until(&mut ppu, 2, 2);
+        assert_eq!(ppu.peek_register(0x2002) & 0x40, 0x40);
+    }
+
+    #[test]
+    fn palette_reads_are_6_bits_with_open_bus() {
+        let mut ppu = test_ppu(Vertical);
+        write_vram(&mut ppu, 0x3F10, &[0xFF]);
+        write_vram(&mut ppu, 0x3F04, &[0x21]);
+
+        // Leave $C0 on the open bus, then point v at the palette
+        ppu.write_register(0x2006, 0x3F);
+        ppu.write_register(0x2006, 0x00);
+        ppu.write_register(0x2003, 0xC0);
+        assert_eq!(ppu.peek_register(0x2007), 0xFF);
+        assert_eq!(ppu.read_register(0x2007), 0xFF);
+
+        ppu.write_register(0x2006, 0x3F);
+        ppu.write_register(0x2006, 0x14);
+        ppu.write_register(0x2003, 0x80);
+        assert_eq!(ppu.peek_register(0x2007), 0xA1);
+        assert_eq!(ppu.read_register(0x2007), 0xA1);
+    }
+
+    #[test]
+    fn oam_attribute_reads_drop_the_unused_bits() {
+        let mut ppu = test_ppu(Vertical);
+        ppu.write_register(0x2003, 0x02);
+        ppu.write_register(0x2004, 0xFF);
+        ppu.write_register(0x2003, 0x02);
+        assert_eq!(ppu.peek_register(0x2004), 0xE3);
+        assert_eq!(ppu.read_register(0x2004), 0xE3);
+    }
+
+    #[test]
+    fn registers_repeat_every_8_bytes() {
+        let mut ppu = test_ppu(Vertical);
+        ppu.write_register(0x3FFE, 0x21);
+        ppu.write_register(0x200E, 0x08);
+        ppu.write_register(0x3007, 0x5A);
+        assert_eq!(ppu.vram[0x108], 0x5A);
+        assert_eq!(ppu.peek_register(0x200F), ppu.peek_register(0x2007));
+    }
+}
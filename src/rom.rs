@@ -1,48 +1,281 @@
-use crate::cartridge::CartridgeInfo;
+use crate::cartridge::{CartridgeInfo, HeaderFormat, Mirroring, Timing};
+use crate::database::{Correction, GameDatabase};
+use crate::error::NesError;
+use crate::nes::Region;
+use crate::patch::apply_patch;
+use std::fmt;
 
 pub struct Rom {
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
-    pub mapper: u8,
+    // 512 bytes that get loaded into $7000-$71FF
+    pub trainer: Option<Vec<u8>>,
+    pub mapper: u16,
+    pub submapper: u8,
     pub cartridge_info: CartridgeInfo,
+    pub warnings: Vec<RomWarning>,
+}
+
+// Problems with the image that still leave it loadable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RomWarning {
+    // Bytes past the end of the CHR ROM that the header does not account for
+    TrailingData(usize),
+}
+
+impl fmt::Display for RomWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RomWarning::TrailingData(size) => {
+                write!(f, "{} bytes after the CHR ROM were ignored", size)
+            }
+        }
+    }
+}
+
+// A summary of the cartridge for display
+#[derive(Debug, Clone)]
+pub struct RomInfo {
+    pub header_format: HeaderFormat,
+    pub prg_rom_size: usize,
+    pub chr_rom_size: usize,
+    pub chr_ram_size: usize,
+    pub prg_ram_size: usize,
+    pub mapper: u16,
+    pub submapper: u8,
+    pub mirroring: Mirroring,
+    pub has_battery_backed_ram: bool,
+    pub has_trainer: bool,
+    pub region: Region,
+}
+
+impl fmt::Display for RomInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} header, mapper {}.{}, PRG ROM {} KB, CHR ROM {} KB, CHR RAM {} KB, PRG RAM {} KB, \
+             {:?} mirroring, {:?}",
+            self.header_format,
+            self.mapper,
+            self.submapper,
+            self.prg_rom_size / 1024,
+            self.chr_rom_size / 1024,
+            self.chr_ram_size / 1024,
+            self.prg_ram_size / 1024,
+            self.mirroring,
+            self.region,
+        )?;
+        if self.has_battery_backed_ram {
+            write!(f, ", battery")?;
+        }
+        if self.has_trainer {
+            write!(f, ", trainer")?;
+        }
+        Ok(())
+    }
 }
 
 impl Rom {
-    pub fn new(raw_bytes: &Vec<u8>) -> Self {
+    pub fn new(raw_bytes: &[u8]) -> Result<Self, NesError> {
         if raw_bytes.len() < 16 {
-            panic!("File is too small");
+            return Err(NesError::FileTooSmall(raw_bytes.len()));
         }
 
         let mut header = [0u8; 16];
         header.copy_from_slice(&raw_bytes[0..16]);
 
         if &header[0..4] != b"NES\x1a" {
-            panic!("Not valid iNES file");
+            return Err(NesError::InvalidHeader);
         }
 
-        let prg_rom_size = header[4] as usize * 16384;
-        let chr_rom_size = header[5] as usize * 8192;
+        let cartridge_info = CartridgeInfo::from_header(&header);
+        let is_nes2 = cartridge_info.header_format == HeaderFormat::Nes2;
+
+        let (prg_rom_size, chr_rom_size) = if is_nes2 {
+            (
+                nes2_rom_size(header[4], header[9] & 0x0F, 16384),
+                nes2_rom_size(header[5], header[9] >> 4, 8192),
+            )
+        } else {
+            (header[4] as usize * 16384, header[5] as usize * 8192)
+        };
 
-        let flags_6 = raw_bytes[6];
-        let flags_7 = raw_bytes[7];
+        let flags_6 = header[6];
+        let flags_7 = header[7];
 
-        let prg_rom_start = 16;
-        let prg_rom_end = prg_rom_start + prg_rom_size;
+        let trainer_size = if cartridge_info.has_trainer { 512 } else { 0 };
+        let prg_rom_start: usize = 16 + trainer_size;
+        let prg_rom_end = prg_rom_start.saturating_add(prg_rom_size);
         let chr_rom_start = prg_rom_end;
-        let chr_rom_end = chr_rom_start + chr_rom_size;
+        let chr_rom_end = chr_rom_start.saturating_add(chr_rom_size);
 
+        if raw_bytes.len() < chr_rom_end {
+            return Err(NesError::TruncatedRom {
+                expected: chr_rom_end,
+                actual: raw_bytes.len(),
+            });
+        }
+
+        if prg_rom_size == 0 {
+            return Err(NesError::MissingPrgRom);
+        }
+
+        let trainer = cartridge_info
+            .has_trainer
+            .then(|| raw_bytes[16..prg_rom_start].to_vec());
         let prg_rom = raw_bytes[prg_rom_start..prg_rom_end].to_vec();
         let chr_rom = raw_bytes[chr_rom_start..chr_rom_end].to_vec();
 
-        let mapper = (flags_7 & 0xF0) | (flags_6 >> 4);
+        let mut warnings = Vec::new();
+        if raw_bytes.len() > chr_rom_end {
+            warnings.push(RomWarning::TrailingData(raw_bytes.len() - chr_rom_end));
+        }
 
-        let cartridge_info = CartridgeInfo::from_header(&header);
+        let (mapper, submapper) = match cartridge_info.header_format {
+            // Flags 7 is junk in these headers, so only the low nibble of the mapper is known
+            HeaderFormat::Archaic | HeaderFormat::Dirty => ((flags_6 >> 4) as u16, 0),
+            HeaderFormat::INes => (((flags_7 & 0xF0) | (flags_6 >> 4)) as u16, 0),
+            HeaderFormat::Nes2 => (
+                ((header[8] & 0x0F) as u16) << 8 | (flags_7 & 0xF0) as u16 | (flags_6 >> 4) as u16,
+                header[8] >> 4,
+            ),
+        };
 
-        Rom {
+        Ok(Rom {
             prg_rom,
             chr_rom,
+            trainer,
             mapper,
+            submapper,
             cartridge_info,
+            warnings,
+        })
+    }
+
+    // Applies the patches in order to the raw image, then parses the result
+    pub fn with_patches(raw_bytes: &[u8], patches: &[&[u8]]) -> Result<Self, NesError> {
+        let mut image = raw_bytes.to_vec();
+        for patch in patches {
+            image = apply_patch(&image, patch)?;
         }
+        Rom::new(&image)
+    }
+
+    // Dumps are identified by the PRG ROM followed by the CHR ROM, leaving out the header and
+    // trainer
+    pub fn crc32(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&self.prg_rom);
+        hasher.update(&self.chr_rom);
+        hasher.finalize()
+    }
+
+    pub fn sha1(&self) -> String {
+        let mut hasher = sha1_smol::Sha1::new();
+        hasher.update(&self.prg_rom);
+        hasher.update(&self.chr_rom);
+        hasher.digest().to_string()
+    }
+
+    // Overrides the header with the database entry for this dump, returning what was changed
+    pub fn apply_database(&mut self, database: &GameDatabase) -> Vec<Correction> {
+        let mut corrections = Vec::new();
+        let Some(entry) = database.lookup(self.crc32(), &self.sha1()) else {
+            return corrections;
+        };
+        let info = &mut self.cartridge_info;
+
+        if let Some(mapper) = entry.mapper.filter(|&mapper| mapper != self.mapper) {
+            corrections.push(Correction::Mapper {
+                from: self.mapper,
+                to: mapper,
+            });
+            self.mapper = mapper;
+        }
+        if let Some(submapper) = entry.submapper.filter(|&sub| sub != self.submapper) {
+            corrections.push(Correction::Submapper {
+                from: self.submapper,
+                to: submapper,
+            });
+            self.submapper = submapper;
+        }
+        if let Some(mirroring) = entry
+            .mirroring
+            .filter(|&mirroring| mirroring != info.mirroring)
+        {
+            corrections.push(Correction::Mirroring {
+                from: info.mirroring,
+                to: mirroring,
+            });
+            info.mirroring = mirroring;
+        }
+        if let Some(battery) = entry
+            .has_battery_backed_ram
+            .filter(|&battery| battery != info.has_battery_backed_ram)
+        {
+            corrections.push(Correction::Battery {
+                from: info.has_battery_backed_ram,
+                to: battery,
+            });
+            info.has_battery_backed_ram = battery;
+        }
+        let prg_ram_size = info.prg_ram_size + info.prg_nvram_size;
+        if let Some(size) = entry.prg_ram_size.filter(|&size| size != prg_ram_size) {
+            corrections.push(Correction::PrgRamSize {
+                from: prg_ram_size,
+                to: size,
+            });
+            info.prg_ram_size = size;
+            info.prg_nvram_size = 0;
+        }
+        if let Some(size) = entry.chr_ram_size.filter(|&size| size != info.chr_ram_size) {
+            corrections.push(Correction::ChrRamSize {
+                from: info.chr_ram_size,
+                to: size,
+            });
+            info.chr_ram_size = size;
+        }
+
+        corrections
+    }
+
+    pub fn info(&self) -> RomInfo {
+        RomInfo {
+            header_format: self.cartridge_info.header_format,
+            prg_rom_size: self.prg_rom.len(),
+            chr_rom_size: self.chr_rom.len(),
+            chr_ram_size: self.cartridge_info.chr_ram_size,
+            prg_ram_size: self.cartridge_info.prg_ram_size + self.cartridge_info.prg_nvram_size,
+            mapper: self.mapper,
+            submapper: self.submapper,
+            mirroring: self.cartridge_info.mirroring,
+            has_battery_backed_ram: self.cartridge_info.has_battery_backed_ram,
+            has_trainer: self.trainer.is_some(),
+            region: self.region(),
+        }
+    }
+
+    // The console timing the header asks for, defaulting to NTSC
+    pub fn region(&self) -> Region {
+        match self.cartridge_info.timing {
+            Timing::Ntsc | Timing::MultiRegion => Region::Ntsc,
+            Timing::Pal => Region::Pal,
+            Timing::Dendy => Region::Dendy,
+        }
+    }
+}
+
+// NES 2.0 extends the ROM sizes with a nibble from byte 9. A nibble of $F switches to an
+// exponent-multiplier form, 2^E * (MM * 2 + 1) bytes with the LSB laid out as EEEEEEMM
+fn nes2_rom_size(lsb: u8, msb: u8, unit: usize) -> usize {
+    if msb == 0x0F {
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = (lsb & 0x03) as usize * 2 + 1;
+        2usize
+            .checked_pow(exponent)
+            .and_then(|size| size.checked_mul(multiplier))
+            .unwrap_or(usize::MAX)
+    } else {
+        ((msb as usize) << 8 | lsb as usize) * unit
     }
 }